@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../src/day16.rs"]
+mod day16;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/gen.rs"]
+mod gen;
+
+use day16::{parse_input, part_one, part_two, Valves};
+
+// A generated input the same size as the real ones, so runs can be compared without one
+fn solving(c: &mut Criterion) {
+    let input = gen::generate(16, 0, None).unwrap();
+    let valves = Valves::new(parse_input(&input).unwrap().into_iter());
+    c.bench_function("day16 part one", |b| b.iter(|| part_one(black_box(&valves))));
+    c.bench_function("day16 part two", |b| b.iter(|| part_two(black_box(&valves), 2, 26)));
+}
+
+criterion_group!(benches, solving);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day1.rs"]
+mod day1;
+
+fuzz_target!(|input: &str| {
+    let _ = day1::parse_input(input);
+});
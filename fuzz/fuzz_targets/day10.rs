@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day10.rs"]
+mod day10;
+
+fuzz_target!(|input: &str| {
+    let _ = day10::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day11.rs"]
+mod day11;
+
+fuzz_target!(|input: &str| {
+    let _ = day11::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day12.rs"]
+mod day12;
+
+fuzz_target!(|input: &str| {
+    let _ = day12::parse_input(input);
+});
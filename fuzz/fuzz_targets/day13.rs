@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day13.rs"]
+mod day13;
+
+fuzz_target!(|input: &str| {
+    let _ = day13::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day14.rs"]
+mod day14;
+
+fuzz_target!(|input: &str| {
+    let _ = day14::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day15.rs"]
+mod day15;
+
+fuzz_target!(|input: &str| {
+    let _ = day15::parse_input(input);
+});
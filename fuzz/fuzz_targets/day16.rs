@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day16.rs"]
+mod day16;
+
+fuzz_target!(|input: &str| {
+    let _ = day16::parse_input(input);
+});
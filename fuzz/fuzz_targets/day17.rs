@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day17.rs"]
+mod day17;
+
+fuzz_target!(|input: &str| {
+    let _ = day17::parse_input(input);
+    let _ = day17::parse_pieces(input);
+});
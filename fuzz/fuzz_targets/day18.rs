@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day18.rs"]
+mod day18;
+
+fuzz_target!(|input: &str| {
+    let _ = day18::parse_input(input);
+});
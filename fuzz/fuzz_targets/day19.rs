@@ -0,0 +1,12 @@
+#![feature(int_roundings)]
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day19.rs"]
+mod day19;
+
+fuzz_target!(|input: &str| {
+    let _ = day19::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day2.rs"]
+mod day2;
+
+fuzz_target!(|input: &str| {
+    let _ = day2::parse_input(input);
+});
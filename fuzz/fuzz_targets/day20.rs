@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day20.rs"]
+mod day20;
+
+fuzz_target!(|input: &str| {
+    let _ = day20::parse_input(input);
+});
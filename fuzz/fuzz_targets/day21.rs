@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day21.rs"]
+mod day21;
+
+fuzz_target!(|input: &str| {
+    let _ = day21::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day22.rs"]
+mod day22;
+
+fuzz_target!(|input: &str| {
+    let _ = day22::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day23.rs"]
+mod day23;
+
+fuzz_target!(|input: &str| {
+    let _ = day23::parse_input(input);
+});
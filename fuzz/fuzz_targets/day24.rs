@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day24.rs"]
+mod day24;
+
+fuzz_target!(|input: &str| {
+    let _ = day24::parse_input(input);
+});
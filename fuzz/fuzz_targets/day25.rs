@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day25.rs"]
+mod day25;
+
+fuzz_target!(|input: &str| {
+    let _ = day25::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day3.rs"]
+mod day3;
+
+fuzz_target!(|input: &str| {
+    let _ = day3::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day4.rs"]
+mod day4;
+
+fuzz_target!(|input: &str| {
+    let _ = day4::parse_input(input);
+});
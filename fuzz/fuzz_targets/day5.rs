@@ -0,0 +1,12 @@
+#![feature(iter_collect_into)]
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day5.rs"]
+mod day5;
+
+fuzz_target!(|input: &str| {
+    let _ = day5::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day7.rs"]
+mod day7;
+
+fuzz_target!(|input: &str| {
+    let _ = day7::parse_input(input);
+});
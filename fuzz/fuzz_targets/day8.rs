@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day8.rs"]
+mod day8;
+
+fuzz_target!(|input: &str| {
+    let _ = day8::parse_input(input);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code, unused_attributes)]
+#[path = "../../src/day9.rs"]
+mod day9;
+
+fuzz_target!(|input: &str| {
+    let _ = day9::parse_input(input);
+});
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Seeds each fuzz target's corpus with the example input for its day. Run it before `cargo fuzz run dayN`
+set -e
+cd "$(dirname "$0")"
+for input in ../inputs/day*_input_test.txt; do
+    day=$(basename "$input" _input_test.txt)
+    [ -f "fuzz_targets/$day.rs" ] || continue
+    mkdir -p "corpus/$day"
+    cp "$input" "corpus/$day/"
+done
@@ -0,0 +1,89 @@
+mod gen;
+mod matrix;
+mod submit;
+
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(about = "Tooling around the daily solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a random, well-formed puzzle input for a day
+    Gen {
+        day: u8,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// What this scales differs per day, each day has a sensible default
+        #[arg(long)]
+        size: Option<usize>,
+    },
+    /// Run every day over every dayN_*.txt under a directory and compare the results. The
+    /// solvers must already be built in the same profile as this binary
+    Matrix {
+        dir: PathBuf,
+        /// Seconds before a run counts as timed out
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Submit an answer, skipping any the submissions log already knows are wrong
+    Submit {
+        #[arg(long)]
+        day: u8,
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+        /// Submit this instead of the solver's answer
+        #[arg(long)]
+        answer: Option<String>,
+        #[arg(long, default_value_t = 2022)]
+        year: u16,
+        /// Point this at a mock server to try submissions out
+        #[arg(long, default_value = "https://adventofcode.com")]
+        base_url: String,
+        #[arg(long, default_value = "submissions.log")]
+        log: PathBuf,
+    },
+}
+
+fn main() {
+    match Cli::parse().command {
+        Command::Gen { day, seed, size } => {
+            let Some(input) = gen::generate(day, seed, size) else {
+                eprintln!("There is no input generator for day {day}");
+                exit(1);
+            };
+            println!("{input}");
+        },
+        Command::Matrix { dir, timeout } => {
+            match matrix::run(&dir, Duration::from_secs(timeout)) {
+                Ok(true) => (),
+                Ok(false) => exit(1),
+                Err(e) => {
+                    eprintln!("Could not run the matrix over {}: {e}", dir.display());
+                    exit(1);
+                },
+            }
+        },
+        Command::Submit { day, part, answer, year, base_url, log } => {
+            match submit::run(day, part, answer, year, &base_url, &log) {
+                Ok(submit::Outcome::Correct) => println!("{}", submit::Outcome::Correct),
+                Ok(outcome) => {
+                    println!("{outcome}");
+                    exit(1);
+                },
+                Err(e) => {
+                    eprintln!("{e}");
+                    exit(1);
+                },
+            }
+        },
+    }
+}
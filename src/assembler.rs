@@ -0,0 +1,122 @@
+use std::ops::RangeInclusive;
+
+use super::{Instruction, CYCLE_COUNT, PIXEL_WIDTH};
+
+// Any X outside this lights nothing, same as its ends do, so there's no need to look further
+const X_RANGE: RangeInclusive<i32> = -2..=PIXEL_WIDTH as i32 + 1;
+
+/// Reads a target screen, one line per row with `#` for lit pixels. Short or missing rows are
+/// dark.
+pub fn parse_bitmap(image: &str) -> Result<[bool; CYCLE_COUNT], String> {
+    let mut target = [false; CYCLE_COUNT];
+    let rows: Vec<&str> = image.trim_matches('\n').lines().collect();
+    if rows.len() > CYCLE_COUNT / PIXEL_WIDTH {
+        return Err(format!("The screen is {} rows tall, got {} rows", CYCLE_COUNT / PIXEL_WIDTH, rows.len()));
+    }
+    for (r, row) in rows.iter().enumerate() {
+        if row.chars().count() > PIXEL_WIDTH {
+            return Err(format!("Row {r} is wider than the {PIXEL_WIDTH} pixel screen"));
+        }
+        for (c, pixel) in row.chars().enumerate() {
+            target[r * PIXEL_WIDTH + c] = pixel == '#';
+        }
+    }
+    Ok(target)
+}
+
+/// Builds a program that draws `target` in exactly `CYCLE_COUNT` cycles. X can only change at the
+/// end of an addx, so it must hold for at least two cycles between changes, and some patterns
+/// can't be drawn at all.
+pub fn assemble(target: &[bool; CYCLE_COUNT]) -> Result<Vec<Instruction>, String> {
+    let x_count = X_RANGE.count();
+    // A state is X along with whether it has been held long enough to change it
+    let state = |x: i32, settled: bool| (x - X_RANGE.start()) as usize * 2 + settled as usize;
+    let x_of = |state: usize| (state / 2) as i32 + X_RANGE.start();
+    let draws = |cycle: usize, x: i32| (((cycle % PIXEL_WIDTH) as i32 - x).abs() <= 1) == target[cycle];
+
+    // reached[c][s] holds the state during the cycle before c that led to s. Cycle 0 has no
+    // previous state, so it points at itself
+    let mut reached = vec![vec![None; x_count * 2]; CYCLE_COUNT];
+    if draws(0, 1) {
+        reached[0][state(1, false)] = Some(state(1, false));
+    }
+    for cycle in 0..CYCLE_COUNT {
+        if reached[cycle].iter().all(Option::is_none) {
+            return Err(format!(
+                "The sprite can't draw row {} column {} along with the pixels before it",
+                cycle / PIXEL_WIDTH,
+                cycle % PIXEL_WIDTH,
+            ));
+        }
+        if cycle + 1 == CYCLE_COUNT {
+            break;
+        }
+        for s in 0..x_count * 2 {
+            if reached[cycle][s].is_none() {
+                continue;
+            }
+            let x = x_of(s);
+            if draws(cycle + 1, x) {
+                reached[cycle + 1][state(x, true)] = Some(s);
+            }
+            if s % 2 == 1 {
+                for next_x in X_RANGE.filter(|&next_x| next_x != x && draws(cycle + 1, next_x)) {
+                    reached[cycle + 1][state(next_x, false)] = Some(s);
+                }
+            }
+        }
+    }
+
+    // Walk back to find X during every cycle
+    let mut xs = [0; CYCLE_COUNT];
+    let mut s = reached[CYCLE_COUNT - 1].iter().position(Option::is_some).unwrap();
+    for cycle in (0..CYCLE_COUNT).rev() {
+        xs[cycle] = x_of(s);
+        s = reached[cycle][s].unwrap();
+    }
+
+    // Each run of the same X ends with the addx that moves it to the next one
+    let mut program = vec!();
+    let mut run_start = 0;
+    for cycle in 1..=CYCLE_COUNT {
+        if cycle < CYCLE_COUNT && xs[cycle] == xs[run_start] {
+            continue;
+        }
+        let run_length = cycle - run_start;
+        if cycle == CYCLE_COUNT {
+            program.extend((0..run_length).map(|_| Instruction::Noop));
+        } else {
+            program.extend((0..run_length - 2).map(|_| Instruction::Noop));
+            program.push(Instruction::Addx(xs[cycle] - xs[run_start]));
+        }
+        run_start = cycle;
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{ocr, process_instructions};
+
+    #[test]
+    fn draws_text() {
+        for text in ["PZGPKPEB", "RUAKHBEK", "BUCACBUZ"] {
+            let program = assemble(&parse_bitmap(&ocr::render(text).unwrap()).unwrap()).unwrap();
+            assert_eq!(program.iter().map(Instruction::cycle_count).sum::<i32>(), CYCLE_COUNT as i32);
+            let (_, screen) = process_instructions(program.into_iter());
+            assert_eq!(ocr::recognise(&screen), Ok(text.to_string()));
+        }
+    }
+
+    #[test]
+    fn impossible_patterns() {
+        // X starts at 1 and holds for two cycles, so column 1 is lit whenever column 0 is
+        let error = assemble(&parse_bitmap("#.").unwrap()).unwrap_err();
+        assert!(error.contains("row 0 column 1"), "{error}");
+        // Isolated pixels need X to move every other cycle
+        let checkerboard = "#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.";
+        assert!(assemble(&parse_bitmap(&format!("..{checkerboard}")[..PIXEL_WIDTH]).unwrap()).is_err());
+        assert!(parse_bitmap(&format!("{checkerboard}#")).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::ops::RangeInclusive;
+
+use super::{Point, Sensor};
+
+const UNCOVERED: [u8; 3] = [0, 0, 0];
+const SENSOR: [u8; 3] = [230, 40, 40];
+const BEACON: [u8; 3] = [60, 110, 240];
+const DISTRESS: [u8; 3] = [40, 230, 40];
+
+impl Sensor {
+    fn covers(&self, point: Point) -> bool {
+        self.location.get_distance(point) <= self.beacon_distance
+    }
+}
+
+/// Draws the area around the sensors the way the puzzle's diagrams do: `S` and `B` for sensors and
+/// beacons, `#` where any of `covering` rules out a beacon and `*` for the distress beacon.
+/// `covering` is usually every sensor, but can be just some of them, as in the puzzle's diagram of
+/// a single sensor's range.
+pub fn render_ascii(
+    sensors: &[Sensor],
+    covering: &[Sensor],
+    xs: RangeInclusive<i32>,
+    ys: RangeInclusive<i32>,
+    distress: Option<Point>,
+) -> String {
+    let label_width = ys.clone().map(|y| y.to_string().len()).max().unwrap_or(0);
+    let column_labels: Vec<String> = xs.clone().map(|x| if x % 5 == 0 { x.to_string() } else { String::new() }).collect();
+    let header_height = column_labels.iter().map(String::len).max().unwrap_or(0);
+
+    let mut lines = vec!();
+    // Column numbers are written downwards, lined up at the bottom
+    for row in 0..header_height {
+        let mut line = " ".repeat(label_width + 1);
+        for label in &column_labels {
+            let skip = header_height - label.len();
+            line.push(if row < skip { ' ' } else { label.as_bytes()[row - skip] as char });
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    for y in ys {
+        let mut line = format!("{y:>label_width$} ");
+        for x in xs.clone() {
+            let point = Point(x, y);
+            line.push(if Some(point) == distress {
+                '*'
+            } else if sensors.iter().any(|s| s.location == point) {
+                'S'
+            } else if sensors.iter().any(|s| s.beacon == point) {
+                'B'
+            } else if covering.iter().any(|s| s.covers(point)) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Draws the square from 0 to `bound` as a binary PPM `size` pixels across. Each pixel shows the
+/// point at its centre, brighter the more sensors cover it, and the sensors, beacons and distress
+/// beacon get marks big enough to see at any scale.
+pub fn render_ppm(sensors: &[Sensor], bound: i32, size: usize, distress: Option<Point>) -> Vec<u8> {
+    let scale = (bound as f64 + 1.0) / size as f64;
+    let to_pixel = |Point(x, y): Point| ((x as f64 / scale) as i64, (y as f64 / scale) as i64);
+    let mut pixels = vec![UNCOVERED; size * size];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let point = Point(((i % size) as f64 * scale + scale / 2.0) as i32, ((i / size) as f64 * scale + scale / 2.0) as i32);
+        let count = sensors.iter().filter(|s| s.covers(point)).count().min(6) as u8;
+        if count > 0 {
+            *pixel = [40 + 30 * count; 3];
+        }
+    }
+
+    let mut mark = |point: Point, radius: i64, colour: [u8; 3]| {
+        let (px, py) = to_pixel(point);
+        for y in py - radius..=py + radius {
+            for x in px - radius..=px + radius {
+                if (0..size as i64).contains(&x) && (0..size as i64).contains(&y) {
+                    pixels[y as usize * size + x as usize] = colour;
+                }
+            }
+        }
+    };
+    for sensor in sensors {
+        mark(sensor.location, 1, SENSOR);
+        mark(sensor.beacon, 1, BEACON);
+    }
+    if let Some(point) = distress {
+        mark(point, 4, DISTRESS);
+    }
+
+    let mut image = format!("P6\n{size} {size}\n255\n").into_bytes();
+    image.extend(pixels.iter().flatten());
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::parse_input;
+
+    #[test]
+    fn matches_the_puzzle() {
+        let sensors = parse_input(include_str!("../inputs/day15_input_test.txt")).unwrap();
+        // The puzzle shows what the sensor at 8,7 covers
+        let covering: Vec<Sensor> = sensors.iter()
+            .filter(|s| s.location == Point(8, 7))
+            .map(|s| Sensor::new(s.location, s.beacon))
+            .collect();
+        // Starts on the line after the quote so the header keeps its indent
+        let expected = "
+               1    1    2    2
+     0    5    0    5    0    5
+-2 ..........#.................
+-1 .........###................
+ 0 ....S...#####...............
+ 1 .......#######........S.....
+ 2 ......#########S............
+ 3 .....###########SB..........
+ 4 ....#############...........
+ 5 ...###############..........
+ 6 ..#################.........
+ 7 .#########S#######S#........
+ 8 ..#################.........
+ 9 ...###############..........
+10 ....B############...........
+11 ..S..###########............
+12 ......#########.............
+13 .......#######..............
+14 ........#####.S.......S.....
+15 B........###................
+16 ..........#SB...............
+17 ................S..........B
+18 ....S.......................
+19 ............................
+20 ............S......S........
+21 ............................
+22 .......................B....";
+        assert_eq!(render_ascii(&sensors, &covering, -2..=25, -2..=22, None), expected[1..]);
+    }
+
+    #[test]
+    fn marks_the_distress_beacon() {
+        let sensors = parse_input(include_str!("../inputs/day15_input_test.txt")).unwrap();
+        let picture = render_ascii(&sensors, &sensors, 0..=20, 0..=20, Some(Point(14, 11)));
+        let uncovered: Vec<(usize, usize)> = picture.lines().skip(2)
+            .enumerate()
+            .flat_map(|(y, line)| line[3..].char_indices().filter(|&(_, c)| c == '.' || c == '*').map(move |(x, _)| (x, y)))
+            .collect();
+        assert_eq!(uncovered, [(14, 11)]);
+
+        let image = render_ppm(&sensors, 20, 21, Some(Point(14, 11)));
+        let header = b"P6\n21 21\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 21 * 21 * 3);
+        let pixel = |x: usize, y: usize| &image[header.len() + (y * 21 + x) * 3..][..3];
+        assert_eq!(pixel(14, 11), DISTRESS);
+        assert_eq!(pixel(8, 7), SENSOR);
+        assert_eq!(pixel(0, 0), [40 + 30; 3]);
+    }
+}
@@ -3,17 +3,23 @@ use std::time::Instant;
 
 const FILE_PATH: &str = "inputs/day1_input.txt";
 
-fn main() {
-    let start = Instant::now();
-    let contents = fs::read_to_string(FILE_PATH).unwrap();
-    let parsed_input = contents
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Vec<i32>>, String> {
+    in_str
         .trim()
         .split("\n\n")
         .map(|l|
              l.split('\n')
-             .map(|f| f.parse().unwrap())
-        );
-    let carries = get_sorted_sums(parsed_input);
+             .map(|f| f.parse().map_err(|_| format!("Invalid calorie count {f:?}")))
+             .collect()
+        )
+        .collect()
+}
+
+fn main() {
+    let start = Instant::now();
+    let contents = fs::read_to_string(FILE_PATH).unwrap();
+    let parsed_input = parse_input(&contents).expect("Could not parse input for day1");
+    let carries = get_sorted_sums(parsed_input.into_iter().map(Vec::into_iter));
 
     println!("Elapsed: {:?}", start.elapsed());
     println!("D1P1: {:?}", carries[0]);
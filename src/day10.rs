@@ -1,75 +1,304 @@
 #![feature(slice_concat_trait)]
 
+mod assembler;
+mod ocr;
+
+use std::env;
+use std::fmt;
 use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::exit;
 use std::time::Instant;
 
 const FILE_PATH: &str = "inputs/day10_input.txt";
 const PIXEL_WIDTH: usize = 40;
 const CYCLE_COUNT: usize = 240;
 
-#[derive(Debug)]
-enum Instruction { Noop, Addx(i32) }
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Instruction { Noop, Addx(i32) }
 
 impl Instruction {
-    fn from_str(in_str: &str) -> Self {
-        match in_str {
-            "noop" => Instruction::Noop,
-            _ => {
-                Instruction::Addx(in_str.split_whitespace().nth(1).unwrap().parse().unwrap())
+    fn from_str(in_str: &str) -> Result<Self, String> {
+        match in_str.split_once(' ') {
+            None if in_str == "noop" => Ok(Instruction::Noop),
+            Some(("addx", v)) => {
+                Ok(Instruction::Addx(v.parse().map_err(|_| format!("Invalid addx operand {in_str:?}"))?))
             }
+            _ => Err(format!("Unknown instruction {in_str:?}")),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(v) => write!(f, "addx {v}"),
         }
     }
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Instruction>, String> {
+    in_str.trim().lines().map(Instruction::from_str).collect()
+}
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--assemble" || arg == "--assemble-bitmap") {
+        let Some(target) = args.get(i + 1) else {
+            eprintln!("{} needs something to draw", args[i]);
+            exit(1);
+        };
+        let image = match args[i].as_str() {
+            "--assemble" => ocr::render(target),
+            _ => fs::read_to_string(target).map_err(|e| format!("Could not read {target}: {e}")),
+        };
+        match image.and_then(|image| assembler::parse_bitmap(&image)).and_then(|target| assembler::assemble(&target)) {
+            Ok(program) => program.iter().for_each(|instruction| println!("{instruction}")),
+            Err(e) => {
+                eprintln!("{e}");
+                exit(1);
+            },
+        }
+        return;
+    }
+
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day10");
-    let parsed = contents.trim().lines().map(Instruction::from_str);
-    let (p1, p2) = process_instructions(parsed);
+    let parsed = parse_input(&contents).expect("Could not parse input for day10");
+    if args.iter().any(|arg| arg == "--debug") {
+        let stdin = io::stdin();
+        let prompt = stdin.is_terminal();
+        debug(&mut Cpu::new(parsed.into_iter()), stdin.lock(), &mut io::stdout(), prompt).expect("Could not run the debugger");
+        return;
+    }
+    let (p1, screen) = process_instructions(parsed.into_iter());
+    let p2 = ocr::recognise(&screen);
     println!("Elapsed: {:?}", start.elapsed());
     println!("D10P1: {p1:?}");
-    println!("D10P2: {p2}");
+    match p2 {
+        Ok(p2) => println!("D10P2: {p2:?}"),
+        // Still show the screen so it can be read by eye
+        Err(e) => {
+            eprintln!("{e}");
+            println!("D10P2: {screen}");
+        },
+    }
 }
 
-fn process_instructions(instructions: impl Iterator<Item = Instruction>) -> (i32, String) {
-    let mut cycle = 0;
-    let mut x_reg: i32 = 1;
-    let mut signal_sum = 0;
-    let mut pixel_output = [' '; CYCLE_COUNT];
-
-    let mut consume_cycles = |count: i32, add: i32| {
-        for _ in 0..count {
-            cycle += 1;
-            let mod_cycle = (cycle - 1) % PIXEL_WIDTH as i32;
-            if mod_cycle == x_reg || mod_cycle == x_reg + 1 || mod_cycle == x_reg - 1 {
-                pixel_output[cycle as usize - 1] = '#'
-            }
-            if (cycle - 20) % 40 == 0 {
-                signal_sum += cycle * x_reg;
+struct Cpu<I: Iterator<Item = Instruction>> {
+    instructions: I,
+    // The instruction being run and how many of its cycles have gone by
+    current: Option<(Instruction, i32)>,
+    cycle: i32,
+    x_reg: i32,
+    signal_sum: i32,
+    pixel_output: [char; CYCLE_COUNT],
+}
+
+// What happened during a single cycle
+struct Tick {
+    cycle: i32,
+    x_reg: i32,
+    beam: usize,
+    lit: bool,
+    instruction: Instruction,
+    instruction_cycle: i32,
+}
+
+impl Instruction {
+    fn cycle_count(&self) -> i32 {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Instruction>> Cpu<I> {
+    fn new(instructions: I) -> Self {
+        Cpu { instructions, current: None, cycle: 0, x_reg: 1, signal_sum: 0, pixel_output: [' '; CYCLE_COUNT] }
+    }
+
+    // Runs a single cycle, or returns None once the program has finished
+    fn tick(&mut self) -> Option<Tick> {
+        if self.current.is_none() {
+            self.current = Some((self.instructions.next()?, 0));
+        }
+        let (instruction, done) = self.current.as_mut().unwrap();
+        *done += 1;
+        self.cycle += 1;
+
+        let beam = self.cycle as usize - 1;
+        let mod_cycle = beam as i32 % PIXEL_WIDTH as i32;
+        let lit = (mod_cycle - self.x_reg).abs() <= 1;
+        if lit && beam < CYCLE_COUNT {
+            self.pixel_output[beam] = '#';
+        }
+        if (self.cycle - 20) % 40 == 0 {
+            self.signal_sum += self.cycle * self.x_reg;
+        }
+
+        let tick = Tick { cycle: self.cycle, x_reg: self.x_reg, beam, lit, instruction: *instruction, instruction_cycle: *done };
+        if *done == instruction.cycle_count() {
+            if let Instruction::Addx(v) = instruction {
+                self.x_reg += *v;
             }
+            self.current = None;
         }
-        x_reg += add;
-    };
+        Some(tick)
+    }
 
-    for instruction in instructions {
-        match instruction {
-            Instruction::Noop => {
-                consume_cycles(1, 0);
-            },
-            Instruction::Addx(v) => { 
-                consume_cycles(2, v);
+    fn screen(&self) -> String {
+        let mut str = String::new();
+        for (i, c) in self.pixel_output.iter().enumerate() {
+            if  i % PIXEL_WIDTH == 0 {
+                str.push('\n');
             }
+            str.push(*c);
+        }
+        str
+    }
+}
+
+fn process_instructions(instructions: impl Iterator<Item = Instruction>) -> (i32, String) {
+    let mut cpu = Cpu::new(instructions);
+    while cpu.tick().is_some() {}
+    (cpu.signal_sum, cpu.screen())
+}
+
+#[derive(Debug)]
+enum Breakpoint {
+    Cycle(i32),
+    XReg(i32),
+}
+
+impl Breakpoint {
+    fn hit(&self, tick: &Tick) -> bool {
+        match *self {
+            Breakpoint::Cycle(cycle) => tick.cycle == cycle,
+            Breakpoint::XReg(x) => tick.x_reg == x,
         }
     }
+}
+
+fn show_tick(tick: &Tick, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "cycle {:>3}  X={:<4} beam row {} col {:<2} {}  {} ({}/{})",
+        tick.cycle,
+        tick.x_reg,
+        tick.beam / PIXEL_WIDTH,
+        tick.beam % PIXEL_WIDTH,
+        if tick.lit { '#' } else { '.' },
+        tick.instruction,
+        tick.instruction_cycle,
+        tick.instruction.cycle_count(),
+    )
+}
 
+const DEBUG_HELP: &str = "\
+step [n]            run n cycles (default 1), printing each one
+continue            run until a breakpoint is hit or the program ends
+break cycle <n>     stop during cycle n
+break x <v>         stop during any cycle where X is v
+breakpoints         list the breakpoints
+delete <i>          remove breakpoint i
+screen              show what has been drawn so far
+quit";
 
-    let mut str = String::new();
-    for (i, c) in pixel_output.iter().enumerate() {
-        if  i % PIXEL_WIDTH == 0 {
-            str.push('\n');
+// Reads commands from `commands` until they run out or say to quit, so it works interactively or
+// from a script
+fn debug<I: Iterator<Item = Instruction>>(cpu: &mut Cpu<I>, commands: impl BufRead, out: &mut impl Write, prompt: bool) -> io::Result<()> {
+    let mut breakpoints: Vec<Breakpoint> = vec!();
+    let mut finished = false;
+    writeln!(out, "{DEBUG_HELP}")?;
+    let mut lines = commands.lines();
+    loop {
+        if prompt {
+            write!(out, "(day10) ")?;
+            out.flush()?;
+        }
+        let Some(line) = lines.next() else { break };
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let mut run = |steps: Option<usize>, out: &mut dyn Write| -> io::Result<()> {
+            for i in 0.. {
+                if steps.is_some_and(|steps| i >= steps) {
+                    break;
+                }
+                let Some(tick) = cpu.tick() else {
+                    if !finished {
+                        writeln!(out, "The program finished after {} cycles", cpu.cycle)?;
+                        finished = true;
+                    }
+                    break;
+                };
+                if let Some(i) = breakpoints.iter().position(|b| b.hit(&tick)) {
+                    write!(out, "breakpoint {i} ({:?}): ", breakpoints[i])?;
+                    show_tick(&tick, out)?;
+                    break;
+                }
+                if steps.is_some() {
+                    show_tick(&tick, out)?;
+                }
+            }
+            Ok(())
+        };
+        match words[..] {
+            [] => (),
+            ["step" | "s"] => run(Some(1), out)?,
+            ["step" | "s", n] => match n.parse() {
+                Ok(n) => run(Some(n), out)?,
+                Err(_) => writeln!(out, "Expected a number of cycles, got {n:?}")?,
+            },
+            ["continue" | "c"] => run(None, out)?,
+            ["break" | "b", kind @ ("cycle" | "x"), v] => match v.parse() {
+                Ok(v) => {
+                    breakpoints.push(if kind == "cycle" { Breakpoint::Cycle(v) } else { Breakpoint::XReg(v) });
+                    writeln!(out, "breakpoint {}: {:?}", breakpoints.len() - 1, breakpoints.last().unwrap())?;
+                },
+                Err(_) => writeln!(out, "Expected a number, got {v:?}")?,
+            },
+            ["breakpoints"] => {
+                for (i, breakpoint) in breakpoints.iter().enumerate() {
+                    writeln!(out, "breakpoint {i}: {breakpoint:?}")?;
+                }
+            },
+            ["delete", i] => match i.parse::<usize>() {
+                Ok(i) if i < breakpoints.len() => {
+                    breakpoints.remove(i);
+                },
+                _ => writeln!(out, "There is no breakpoint {i}")?,
+            },
+            ["screen"] => writeln!(out, "{}", cpu.screen().trim_start_matches('\n'))?,
+            ["quit" | "q"] => break,
+            _ => writeln!(out, "Unknown command {line:?}\n{DEBUG_HELP}")?,
         }
-        str.push(*c);
     }
+    Ok(())
+}
 
-    (signal_sum, str)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_debugging() {
+        let program = parse_input("noop\naddx 3\naddx -5\nnoop").unwrap();
+        let mut cpu = Cpu::new(program.into_iter());
+        let script = "break x 4\ncontinue\nstep 2\ndelete 0\nstep\ncontinue\nscreen\n";
+        let mut out = vec!();
+        debug(&mut cpu, script.as_bytes(), &mut out, false).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let trace: Vec<&str> = out.lines().skip(DEBUG_HELP.lines().count()).collect();
+
+        assert_eq!(trace[0], "breakpoint 0: XReg(4)");
+        assert_eq!(trace[1], "breakpoint 0 (XReg(4)): cycle   4  X=4    beam row 0 col 3  #  addx -5 (1/2)");
+        // X is still 4 on the next cycle, so the step stops there
+        assert_eq!(trace[2], "breakpoint 0 (XReg(4)): cycle   5  X=4    beam row 0 col 4  #  addx -5 (2/2)");
+        assert_eq!(trace[3], "cycle   6  X=-1   beam row 0 col 5  .  noop (1/1)");
+        assert_eq!(trace[4], "The program finished after 6 cycles");
+        assert_eq!(trace[5].trim_end(), "#####");
+    }
 }
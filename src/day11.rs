@@ -1,111 +1,417 @@
+use std::env;
 use std::fs;
 use std::collections::VecDeque;
 use std::time::Instant;
 
 use num::integer::lcm;
+use num::traits::{CheckedDiv, CheckedSub, Zero};
+use num::BigUint;
 
 const FILE_PATH: &str = "inputs/day11_input.txt";
 const P1_CYCLE_COUNT: usize = 20;
 const P2_CYCLE_COUNT: usize = 10_000;
 
-#[derive(Clone)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub(crate) struct Monkey {
     items: VecDeque<u64>,
     inspection_count: usize,
     divisor: u64,
     true_index: usize,
     false_index: usize,
-    operand: Option<u64>,
-    operator: Operator,
+    operation: Expr,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Operator {
     Add,
+    Sub,
     Mult,
+    Div,
+    Mod,
 }
 
-fn get_operation(param_str: &str) -> (Operator, Option<u64>) {
-    let mut parameters = param_str.split_whitespace().skip(1);
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Old,
+    Number(u64),
+    Binary(Box<Expr>, Operator, Box<Expr>),
+}
 
-    let operator = match parameters.next().unwrap() {
-        "*" => Operator::Mult,
-        "+" => Operator::Add,
-        _ => panic!()
-    };
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Token {
+    Old,
+    Number(u64),
+    Operator(Operator),
+    Open,
+    Close,
+}
 
-    let raw_operand = parameters.next().unwrap();
-    let operand = match raw_operand{
-        "old" => None,
-        _ => Some(raw_operand.parse().unwrap()),
-    };
+fn tokenise(param_str: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec!();
+    let mut chars = param_str.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let token = match c {
+            ' ' => continue,
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Operator(Operator::Sub),
+            '*' => Token::Operator(Operator::Mult),
+            '/' => Token::Operator(Operator::Div),
+            '%' => Token::Operator(Operator::Mod),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ if param_str[i..].starts_with("old") => {
+                chars.nth(1);
+                Token::Old
+            },
+            _ if c.is_ascii_digit() => {
+                let mut end = i + 1;
+                while let Some(&(j, d)) = chars.peek() {
+                    if !d.is_ascii_digit() {
+                        break;
+                    }
+                    end = j + 1;
+                    chars.next();
+                }
+                Token::Number(param_str[i..end].parse().map_err(|_| format!("Invalid operand {:?}", &param_str[i..end]))?)
+            },
+            _ => return Err(format!("Unexpected {c:?} in operation {param_str:?}")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
 
-    (operator, operand)
+// expr := term (("+" | "-") term)*, term := factor (("*" | "/" | "%") factor)*,
+// factor := "old" | number | "(" expr ")"
+fn parse_expr(tokens: &[Token], i: &mut usize, operators: &[&[Operator]]) -> Result<Expr, String> {
+    let Some((these, tighter)) = operators.split_first() else {
+        let token = tokens.get(*i).ok_or("The operation ended early")?;
+        *i += 1;
+        return match token {
+            Token::Old => Ok(Expr::Old),
+            Token::Number(n) => Ok(Expr::Number(*n)),
+            Token::Open => {
+                let inner = parse_expr(tokens, i, &PRECEDENCE)?;
+                if tokens.get(*i) != Some(&Token::Close) {
+                    return Err("Unclosed parenthesis in operation".to_string());
+                }
+                *i += 1;
+                Ok(inner)
+            },
+            _ => Err(format!("Expected a value in the operation, got {token:?}")),
+        };
+    };
+    let mut lhs = parse_expr(tokens, i, tighter)?;
+    while let Some(&Token::Operator(operator)) = tokens.get(*i) {
+        if !these.contains(&operator) {
+            break;
+        }
+        *i += 1;
+        let rhs = parse_expr(tokens, i, tighter)?;
+        lhs = Expr::Binary(Box::new(lhs), operator, Box::new(rhs));
+    }
+    Ok(lhs)
 }
 
-fn get_test<'a>(mut lines: impl Iterator<Item = &'a str>) -> (u64, usize, usize) {
-    let divisor: u64 = lines.next().unwrap().split_whitespace().last().unwrap().parse().unwrap();
-    let true_monkey: usize = lines.next().unwrap().split_whitespace().last().unwrap().parse().unwrap();
-    let false_monkey: usize = lines.next().unwrap().split_whitespace().last().unwrap().parse().unwrap();
+// Loosest binding first
+const PRECEDENCE: [&[Operator]; 2] = [&[Operator::Add, Operator::Sub], &[Operator::Mult, Operator::Div, Operator::Mod]];
 
-    (divisor, true_monkey, false_monkey)
+fn get_operation(param_str: &str) -> Result<Expr, String> {
+    let tokens = tokenise(param_str)?;
+    let mut i = 0;
+    let expr = parse_expr(&tokens, &mut i, &PRECEDENCE)?;
+    if i < tokens.len() {
+        return Err(format!("Unexpected {:?} in operation {param_str:?}", tokens[i]));
+    }
+    Ok(expr)
 }
-impl Monkey {
-    fn operation(&self, v: u64) -> u64 {
-        let operand = if self.operand.is_none() { v } else { self.operand.unwrap() };
-        match self.operator {
-            Operator::Add => v + operand,
-            Operator::Mult => v * operand
+
+impl Expr {
+    fn needs_exact(&self) -> bool {
+        match self {
+            Expr::Binary(lhs, operator, rhs) => {
+                matches!(operator, Operator::Sub | Operator::Div | Operator::Mod) || lhs.needs_exact() || rhs.needs_exact()
+            },
+            _ => false,
         }
     }
 
-    fn cycle(&mut self, worry_divisor: u64) -> Option<(u64, usize)> {
+    // With a modulus everything is worked out mod it, which can't be done for division. Subtraction
+    // could wrap around, but then a worry level going negative would pass unnoticed
+    fn evaluate(&self, old: u64, modulus: Option<u64>) -> Result<u64, String> {
+        let Expr::Binary(lhs, operator, rhs) = self else {
+            let v = if let Expr::Number(n) = self { *n } else { old };
+            return Ok(modulus.map_or(v, |m| v % m));
+        };
+        let (a, b) = (lhs.evaluate(old, modulus)?, rhs.evaluate(old, modulus)?);
+        if let Some(m) = modulus {
+            let (a, b, m) = (a as u128, b as u128, m as u128);
+            return match operator {
+                Operator::Add => Ok(((a + b) % m) as u64),
+                Operator::Mult => Ok((a * b % m) as u64),
+                Operator::Sub => Err("Cannot subtract worry levels kept modulo a number".to_string()),
+                Operator::Div | Operator::Mod => Err("Cannot divide worry levels kept modulo a number".to_string()),
+            };
+        }
+        match operator {
+            Operator::Add => a.checked_add(b).ok_or("Worry level overflowed"),
+            Operator::Sub => a.checked_sub(b).ok_or("Worry level went negative"),
+            Operator::Mult => a.checked_mul(b).ok_or("Worry level overflowed"),
+            Operator::Div => a.checked_div(b).ok_or("Worry level divided by zero"),
+            Operator::Mod => a.checked_rem(b).ok_or("Worry level divided by zero"),
+        }.map_err(str::to_string)
+    }
+
+    fn evaluate_exact(&self, old: &BigUint) -> Result<BigUint, String> {
+        let Expr::Binary(lhs, operator, rhs) = self else {
+            return Ok(if let Expr::Number(n) = self { BigUint::from(*n) } else { old.clone() });
+        };
+        let (a, b) = (lhs.evaluate_exact(old)?, rhs.evaluate_exact(old)?);
+        match operator {
+            Operator::Add => Ok(a + b),
+            Operator::Sub => a.checked_sub(&b).ok_or("Worry level went negative"),
+            Operator::Mult => Ok(a * b),
+            Operator::Div => a.checked_div(&b).ok_or("Worry level divided by zero"),
+            Operator::Mod if b.is_zero() => Err("Worry level divided by zero"),
+            Operator::Mod => Ok(a % b),
+        }.map_err(str::to_string)
+    }
+}
+
+fn get_test<'a>(mut lines: impl Iterator<Item = &'a str>) -> Result<(u64, usize, usize), String> {
+    let mut last_number = |prefix: &str| {
+        let line = lines.next().ok_or_else(|| format!("Missing {prefix:?}"))?;
+        line.trim().strip_prefix(prefix)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("Expected {prefix:?}, got {line:?}"))
+    };
+    let divisor = last_number("Test: divisible by ")?;
+    let true_monkey = last_number("If true: throw to monkey ")?;
+    let false_monkey = last_number("If false: throw to monkey ")?;
+    if divisor == 0 {
+        return Err("Cannot test divisibility by zero".to_string());
+    }
+
+    Ok((divisor as u64, true_monkey, false_monkey))
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Monkey>, String> {
+    let monkeys: Vec<Monkey> = in_str.trim().split("\n\n").map(Monkey::from_str).collect::<Result<_, _>>()?;
+    if monkeys.iter().any(|m| m.true_index >= monkeys.len() || m.false_index >= monkeys.len()) {
+        return Err("A monkey throws to a monkey that does not exist".to_string());
+    }
+    Ok(monkeys)
+}
+
+impl Monkey {
+    fn cycle(&mut self, worry_divisor: u64, worry_mod: Option<u64>) -> Result<Option<(u64, usize)>, String> {
         if let Some(v) = self.items.pop_front() {
             self.inspection_count += 1;
-            let v = self.operation(v) / worry_divisor;
-            let idx = if v % self.divisor == 0 { self.true_index } else { self.false_index };
-            return Some((v, idx))
+            let v = self.operation.evaluate(v, worry_mod)? / worry_divisor;
+            let idx = if v.is_multiple_of(self.divisor) { self.true_index } else { self.false_index };
+            return Ok(Some((v, idx)))
         };
-        None
+        Ok(None)
     }
 
-    fn from_str(raw_monkey: &str) -> Self {
+    fn from_str(raw_monkey: &str) -> Result<Self, String> {
         let mut lines = raw_monkey.lines().skip(1);
 
-        let (_, items) = lines.next().unwrap().split_once(':').unwrap();
-        let items: VecDeque<u64> = items.split(',').map(|i| i.trim().parse().unwrap()).collect();
+        let items = lines.next().and_then(|l| l.trim().strip_prefix("Starting items:"))
+            .ok_or_else(|| format!("Missing starting items in {raw_monkey:?}"))?;
+        let items: VecDeque<u64> = items.split(',')
+            .map(|i| i.trim())
+            .filter(|i| !i.is_empty())
+            .map(|i| i.parse().map_err(|_| format!("Invalid item {i:?}")))
+            .collect::<Result<_, _>>()?;
 
-        let (_, raw_op) = lines.next().unwrap().split_once('=').unwrap();
-        let (operator, operand) = get_operation(raw_op);
+        let raw_op = lines.next().and_then(|l| l.trim().strip_prefix("Operation: new ="))
+            .ok_or_else(|| format!("Missing operation in {raw_monkey:?}"))?;
+        let operation = get_operation(raw_op)?;
 
-        let (divisor, true_index, false_index) = get_test(lines);
+        let (divisor, true_index, false_index) = get_test(lines)?;
 
-        Monkey { items, operator, operand, divisor, true_index, false_index, inspection_count: 0 }
+        Ok(Monkey { items, operation, divisor, true_index, false_index, inspection_count: 0 })
     }
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day11");
-    let mut monkeys: Vec<Monkey> = contents.trim().split("\n\n").map(Monkey::from_str).collect();
+    let mut monkeys = parse_input(&contents).expect("Could not parse input for day11");
+
+    // --exact <rounds> follows the real worry levels, with part one's relief if --relief is given
+    let args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--exact") {
+        let rounds = args.get(i + 1).and_then(|r| r.parse().ok()).expect("--exact needs a number of rounds");
+        let divisor = if args.iter().any(|arg| arg == "--relief") { 3 } else { 1 };
+        let business = compute_exact(&monkeys, divisor, rounds, |round, items, inspection_counts| {
+            if round == 1 || round == 20 || round % 1000 == 0 {
+                println!("{}", round_report(round, items, inspection_counts));
+            }
+        });
+        println!("Elapsed: {:?}", start.elapsed());
+        println!("Monkey business: {}", business.expect("Could not simulate day11 exactly"));
+        return;
+    }
+
     let mut monkeys2: Vec<Monkey> = monkeys.iter().map(Monkey::clone).collect();
-    let worry_mod = monkeys.iter().map(|m| m.divisor).reduce(lcm).unwrap();
-    let p1 = compute(&mut monkeys, 3, P1_CYCLE_COUNT, worry_mod);
-    let p2 = compute(&mut monkeys2, 1, P2_CYCLE_COUNT, worry_mod);
+    let p1 = compute(&mut monkeys, 3, P1_CYCLE_COUNT).expect("Could not simulate part one for day11");
+    let p2 = compute(&mut monkeys2, 1, P2_CYCLE_COUNT).expect("Could not simulate part two for day11");
     println!("Elapsed: {:?}", start.elapsed());
     println!("D11P1: {p1:?}");
     println!("D11P2: {p2:?}");
 }
 
-fn compute(monkeys: &mut [Monkey], divisor: u64, cycles: usize, worry_mod: u64) -> usize {
+fn compute(monkeys: &mut [Monkey], divisor: u64, cycles: usize) -> Result<usize, String> {
+    // Keeping worry levels modulo the LCM of the divisors leaves every test's result alone, but
+    // only while nothing divides or subtracts them
+    let worry_mod = (divisor == 1 && !monkeys.iter().any(|m| m.operation.needs_exact()))
+        .then(|| monkeys.iter().map(|m| m.divisor).reduce(lcm))
+        .flatten();
     for _ in 0..cycles {
         for i in 0..monkeys.len() {
-            while let Some((value, index)) = monkeys[i].cycle(divisor) {
-                monkeys[index].items.push_back(value % worry_mod);
+            while let Some((value, index)) = monkeys[i].cycle(divisor, worry_mod)? {
+                monkeys[index].items.push_back(value);
             }
         }
     }
 
     monkeys.sort_by(|m1, m2| m2.inspection_count.cmp(&m1.inspection_count));
-    return monkeys[0].inspection_count * monkeys[1].inspection_count;
+    Ok(monkeys[0].inspection_count * monkeys[1].inspection_count)
+}
+
+// Keeps every worry level exact, so it needs no modular arithmetic to be right. Slow, and meant
+// for short runs. `after_round` sees every monkey's items and inspection count after each round
+fn compute_exact(
+    monkeys: &[Monkey],
+    divisor: u64,
+    rounds: usize,
+    mut after_round: impl FnMut(usize, &[VecDeque<BigUint>], &[usize]),
+) -> Result<usize, String> {
+    let mut items: Vec<VecDeque<BigUint>> = monkeys.iter()
+        .map(|m| m.items.iter().map(|&v| BigUint::from(v)).collect())
+        .collect();
+    let mut inspection_counts = vec![0; monkeys.len()];
+    for round in 1..=rounds {
+        for (i, monkey) in monkeys.iter().enumerate() {
+            while let Some(v) = items[i].pop_front() {
+                inspection_counts[i] += 1;
+                let v = monkey.operation.evaluate_exact(&v)? / divisor;
+                let idx = if (&v % monkey.divisor).is_zero() { monkey.true_index } else { monkey.false_index };
+                items[idx].push_back(v);
+            }
+        }
+        after_round(round, &items, &inspection_counts);
+    }
+
+    inspection_counts.sort_by(|c1, c2| c2.cmp(c1));
+    Ok(inspection_counts[0] * inspection_counts[1])
+}
+
+// Words it the way the puzzle does
+fn round_report(round: usize, items: &[VecDeque<BigUint>], inspection_counts: &[usize]) -> String {
+    let mut report = format!("== After round {round} ==");
+    for (i, held) in items.iter().enumerate() {
+        let held: Vec<String> = held.iter().map(BigUint::to_string).collect();
+        report += format!("\nMonkey {i}: {}", held.join(", ")).trim_end();
+    }
+    for (i, count) in inspection_counts.iter().enumerate() {
+        report += &format!("\nMonkey {i} inspected items {count} times.");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Only + and * so worry levels never go negative
+    fn operation() -> impl Strategy<Value = Expr> {
+        prop_oneof![Just(Expr::Old), (1u64..20).prop_map(Expr::Number)].prop_recursive(2, 6, 2, |inner| {
+            (inner.clone(), prop_oneof![Just(Operator::Add), Just(Operator::Mult)], inner)
+                .prop_map(|(lhs, operator, rhs)| Expr::Binary(Box::new(lhs), operator, Box::new(rhs)))
+        })
+    }
+
+    fn monkey(count: usize) -> impl Strategy<Value = Monkey> {
+        (
+            prop::collection::vec_deque(1u64..100, 0..4),
+            operation(),
+            2u64..20,
+            0..count,
+            0..count,
+        ).prop_map(|(items, operation, divisor, true_index, false_index)| {
+            Monkey { items, operation, divisor, true_index, false_index, inspection_count: 0 }
+        })
+    }
+
+    fn monkeys() -> impl Strategy<Value = Vec<Monkey>> {
+        (2usize..=4).prop_flat_map(|count| prop::collection::vec(monkey(count), count))
+            .prop_map(|mut monkeys| {
+                // A monkey never throws to itself
+                let count = monkeys.len();
+                for (i, monkey) in monkeys.iter_mut().enumerate() {
+                    if monkey.true_index == i { monkey.true_index = (i + 1) % count; }
+                    if monkey.false_index == i { monkey.false_index = (i + 1) % count; }
+                }
+                monkeys
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn worry_mod_matches_exact(monkeys in monkeys(), cycles in 1usize..=4) {
+            let expected = compute_exact(&monkeys, 1, cycles, |_, _, _| ());
+            prop_assert_eq!(compute(&mut monkeys.clone(), 1, cycles), expected);
+        }
+    }
+
+    #[test]
+    fn parses_expressions() {
+        let old = || Box::new(Expr::Old);
+        assert_eq!(get_operation("old * 19"), Ok(Expr::Binary(old(), Operator::Mult, Box::new(Expr::Number(19)))));
+        let expr = get_operation(" old * old + 3").unwrap();
+        assert_eq!(expr, Expr::Binary(Box::new(Expr::Binary(old(), Operator::Mult, old())), Operator::Add, Box::new(Expr::Number(3))));
+        assert_eq!(expr.evaluate(5, None), Ok(28));
+        assert_eq!(get_operation("(old + 1) * (old - 1) / 2 % 7").unwrap().evaluate(5, None), Ok(5));
+        assert_eq!(get_operation("10 - old - 1").unwrap().evaluate(4, None), Ok(5));
+        for invalid in ["old +", "(old", "old old", "old ^ 2", "olx", ""] {
+            assert!(get_operation(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn exact_reports() {
+        let monkeys = parse_input(include_str!("../inputs/day11_input_test.txt")).unwrap();
+        let mut reports = vec!();
+        let business = compute_exact(&monkeys, 3, 20, |round, items, counts| reports.push(round_report(round, items, counts)));
+        assert_eq!(business, Ok(10605));
+        assert_eq!(reports[0], "\
+== After round 1 ==
+Monkey 0: 20, 23, 27, 26
+Monkey 1: 2080, 25, 167, 207, 401, 1046
+Monkey 2:
+Monkey 3:
+Monkey 0 inspected items 2 times.
+Monkey 1 inspected items 4 times.
+Monkey 2 inspected items 3 times.
+Monkey 3 inspected items 5 times.");
+        assert!(reports[19].ends_with("Monkey 0 inspected items 101 times.\nMonkey 1 inspected items 95 times.\nMonkey 2 inspected items 7 times.\nMonkey 3 inspected items 105 times."));
+
+        let mut counts_after_20 = vec!();
+        compute_exact(&monkeys, 1, 20, |round, _, counts| if round == 20 { counts_after_20 = counts.to_vec() }).unwrap();
+        assert_eq!(counts_after_20, [99, 97, 8, 103]);
+    }
+
+    #[test]
+    fn subtraction_keeps_worry_exact() {
+        let expr = get_operation("old * 3 - old - 20").unwrap();
+        assert!(expr.needs_exact());
+        assert_eq!(expr.evaluate(17, None), Ok(14));
+        assert!(expr.evaluate(17, Some(7)).is_err());
+        assert!(expr.evaluate(3, None).is_err());
+        assert!(get_operation("old / (old - old)").unwrap().evaluate(3, None).is_err());
+    }
 }
@@ -1,9 +1,7 @@
+use std::env;
 use std::fs;
-use std::thread;
-use std::sync::Arc;
 use std::time::Instant;
 use std::collections::VecDeque;
-use rustc_hash::FxHashSet;
 
 const FILE_PATH: &str = "inputs/day12_input.txt";
 const OFFSETS: [(i32,i32); 4] = [(1, 0), (-1, 0), (0, 1), (0,-1)];
@@ -12,41 +10,60 @@ type Grid<T> = Vec<Vec<T>>;
 
 type Point = (usize, usize);
 
-fn parse_input(in_str: &str) -> (Grid<char>, Point, Point) {
+// Every point along a route, both ends included
+type Path = Vec<Point>;
+
+pub(crate) fn parse_input(in_str: &str) -> Result<(Grid<char>, Point, Point), String> {
     let mut grid = vec!();
-    let (mut start, mut end) = ((0,0), (0,0));
+    let (mut start, mut end) = (None, None);
 
     for line in in_str.lines() {
         let mut row = vec!();
         for (c, char)in line.chars().enumerate() {
             row.push(char);
-            if char == 'S' {
-                start = (grid.len(), c);
-            }
-            if char == 'E' {
-                end = (grid.len(), c);
+            match char {
+                'S' if start.is_none() => start = Some((grid.len(), c)),
+                'E' if end.is_none() => end = Some((grid.len(), c)),
+                'a'..='z' => (),
+                _ => return Err(format!("Unexpected square {char:?} in {line:?}")),
             }
         }
         grid.push(row);
     }
-    (grid, start, end)
+    if grid.iter().any(|row| row.len() != grid[0].len()) {
+        return Err("The heightmap must be rectangular".to_string());
+    }
+    match (start, end) {
+        (Some(start), Some(end)) => Ok((grid, start, end)),
+        _ => Err("The heightmap needs a start and an end".to_string()),
+    }
 }
 
 fn main() {
     let t = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day12");
-    let (grid, start, end) = parse_input(&contents);
-    let arc1 = Arc::new(grid);
-    let arc2 = arc1.clone();
-
-    let p2_handle = thread::spawn(move || calculate_min_path(&arc1, &end, 'a', false));
+    let (grid, start, end) = parse_input(&contents).expect("Could not parse input for day12");
 
-    let p1 = calculate_min_path(&arc2, &start, 'E', true);
-    let p2 = p2_handle.join().expect("Panic occurred during p2");
+    // One search down from the end finds the way up from everywhere
+    let from_end = Search::new(&grid, &[end], |from, to| climbable(to, from));
+    let p1 = from_end.path_from(start);
+    let best_start = lowest_points(&grid).into_iter()
+        .filter(|&point| from_end.distance(point).is_some())
+        .min_by_key(|&point| from_end.distance(point));
+    let p2 = best_start.and_then(|point| from_end.path_from(point));
 
     println!("Elapsed: {:?}", t.elapsed());
-    println!("D12P1: {p1:?}");
-    println!("D12P2: {p2:?}");
+    if env::args().any(|arg| arg == "--route") {
+        for path in p1.iter().chain(p2.iter()) {
+            println!("{}\n", render_path(&grid, path));
+        }
+    }
+    if let Some((r, c)) = best_start {
+        println!("Best start: row {r}, column {c}");
+    }
+    let steps = |path: &Option<Path>| path.as_ref().map_or("no route".to_string(), |path| (path.len() - 1).to_string());
+    println!("D12P1: {}", steps(&p1));
+    println!("D12P2: {}", steps(&p2));
 }
 
 fn get_elevation(ch: char) -> u8 {
@@ -57,34 +74,121 @@ fn get_elevation(ch: char) -> u8 {
     }
 }
 
-fn calculate_min_path(grid: &Grid<char>, start: &Point, end_char: char, ascending: bool) -> u16 {
-    let (width, height) = (grid[0].len(), grid.len());
-    let mut visited: FxHashSet<Point> = FxHashSet::default();
-    let mut to_visit: VecDeque<(Point, u16)> = VecDeque::new();
-    to_visit.push_front((*start, 0));
-
-    while let Some((current, steps)) = to_visit.pop_front() {
-        visited.insert(current);
-        let current_height = get_elevation(grid[current.0][current.1]);
-        for offset in OFFSETS {
-            let r1 = offset.0 + current.0 as i32;
-            let c1 = offset.1 + current.1 as i32;
-            if (r1 < 0 || r1 as usize >= height) || (c1 < 0 || c1 as usize >= width) {
-                continue
-            }
-            let (r1, c1) = (r1 as usize, c1 as usize);
-            let next = (r1, c1);
-            let next_height = get_elevation(grid[r1][c1]);
-            let heights_match = if ascending { next_height <= current_height + 1 } else { current_height <= next_height + 1 };
-            if !heights_match || visited.contains(&next) {
-                continue;
-            }
-            if grid[r1][c1] == end_char {
-                return steps + 1;
+fn climbable(from: u8, to: u8) -> bool {
+    to <= from + 1
+}
+
+// Every square at elevation 'a', the start square included
+fn lowest_points(grid: &Grid<char>) -> Vec<Point> {
+    (0..grid.len())
+        .flat_map(|r| (0..grid[r].len()).map(move |c| (r, c)))
+        .filter(|&(r, c)| get_elevation(grid[r][c]) == b'a')
+        .collect()
+}
+
+// A breadth first search out from any number of sources to every point it can reach
+struct Search {
+    distances: Grid<Option<u16>>,
+    // The neighbour each point was reached from, one step closer to a source
+    came_from: Grid<Option<Point>>,
+}
+
+impl Search {
+    // `can_step` is given the elevations either side of a step, so flipping its arguments searches
+    // the other way
+    fn new(grid: &Grid<char>, sources: &[Point], can_step: impl Fn(u8, u8) -> bool) -> Self {
+        let (width, height) = (grid[0].len(), grid.len());
+        let mut distances = vec![vec![None; width]; height];
+        let mut came_from = vec![vec![None; width]; height];
+        let mut to_visit: VecDeque<Point> = VecDeque::new();
+        for &(r, c) in sources {
+            distances[r][c] = Some(0);
+            to_visit.push_back((r, c));
+        }
+
+        while let Some(current) = to_visit.pop_front() {
+            let current_height = get_elevation(grid[current.0][current.1]);
+            let steps = distances[current.0][current.1].unwrap();
+            for offset in OFFSETS {
+                let r1 = offset.0 + current.0 as i32;
+                let c1 = offset.1 + current.1 as i32;
+                if (r1 < 0 || r1 as usize >= height) || (c1 < 0 || c1 as usize >= width) {
+                    continue
+                }
+                let (r1, c1) = (r1 as usize, c1 as usize);
+                if !can_step(current_height, get_elevation(grid[r1][c1])) || distances[r1][c1].is_some() {
+                    continue;
+                }
+                distances[r1][c1] = Some(steps + 1);
+                came_from[r1][c1] = Some(current);
+                to_visit.push_back((r1, c1));
             }
-            to_visit.push_back((next, steps + 1));
-            visited.insert(next);
         }
+        Search { distances, came_from }
+    }
+
+    fn distance(&self, (r, c): Point) -> Option<u16> {
+        self.distances[r][c]
+    }
+
+    // From `point` back to whichever source is nearest it
+    fn path_from(&self, point: Point) -> Option<Path> {
+        self.distance(point)?;
+        let mut path = vec![point];
+        while let Some((r, c)) = path.last().and_then(|&(r, c)| self.came_from[r][c]) {
+            path.push((r, c));
+        }
+        Some(path)
+    }
+}
+
+// Marks each step of the route with an arrow to the next one, the way the puzzle draws it
+fn render_path(grid: &Grid<char>, path: &Path) -> String {
+    let mut canvas: Grid<char> = grid.iter().map(|row| vec!['.'; row.len()]).collect();
+    for step in path.windows(2) {
+        let ((r0, c0), (r1, c1)) = (step[0], step[1]);
+        canvas[r0][c0] = match (r1 as i32 - r0 as i32, c1 as i32 - c0 as i32) {
+            (-1, _) => '^',
+            (1, _) => 'v',
+            (_, -1) => '<',
+            _ => '>',
+        };
+    }
+    if let Some(&(r, c)) = path.last() {
+        canvas[r][c] = grid[r][c];
+    }
+    canvas.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes() {
+        let (grid, start, end) = parse_input("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi").unwrap();
+        let from_end = Search::new(&grid, &[end], |from, to| climbable(to, from));
+        let up = from_end.path_from(start).unwrap();
+        assert_eq!((up[0], up[up.len() - 1], up.len() - 1), (start, end, 31));
+        assert!(up.windows(2).all(|step| step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1) == 1));
+        let best = lowest_points(&grid).into_iter().filter_map(|point| from_end.distance(point)).min();
+        assert_eq!(best, Some(29));
+        assert_eq!(from_end.distance((4, 0)), Some(29));
+        assert_eq!(from_end.distance(end), Some(0));
+
+        // Searching up from every 'a' at once agrees with searching down from the end
+        let from_lowest = Search::new(&grid, &lowest_points(&grid), climbable);
+        assert_eq!(from_lowest.distance(end), best);
+
+        let (grid, start, end) = parse_input("Sbcdefghijklm\nEyxwvutsrqpon\naaaaaaaaaaaaa").unwrap();
+        let route = Search::new(&grid, &[end], |from, to| climbable(to, from)).path_from(start).unwrap();
+        assert_eq!(render_path(&grid, &route), ">>>>>>>>>>>>v\nE<<<<<<<<<<<<\n.............");
+        // The start square is at elevation 'a' too, here it's the closest one
+        let (grid, start, end) = parse_input("SbcdefghijklmnopqrstuvwxyE\nazzzzzzzzzzzzzzzzzzzzzzzzz").unwrap();
+        let from_end = Search::new(&grid, &[end], |from, to| climbable(to, from));
+        let best = lowest_points(&grid).into_iter().min_by_key(|&point| from_end.distance(point));
+        assert_eq!((best, from_end.distance(start)), (Some(start), Some(25)));
+        let (grid, start, end) = parse_input("SbE").unwrap();
+        assert_eq!(Search::new(&grid, &[end], |from, to| climbable(to, from)).path_from(start), None);
     }
-    panic!("Not Found!")
 }
@@ -1,18 +1,22 @@
 use std::fs;
-use std::fmt::{Debug, Formatter, Result};
+use std::fmt::{self, Debug, Formatter};
 use std::time::Instant;
 use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::CharIndices;
 
 const FILE_PATH: &str = "inputs/day13_input.txt";
+// Parsing, comparing and printing packets all recurse, so deeper lists would overflow the stack
+const MAX_DEPTH: usize = 256;
 
 #[derive(Clone)]
-enum Packet {
+pub(crate) enum Packet {
     List(Vec<Packet>),
     Number(i32),
 }
 
 impl<'a> Debug for Packet {
-    fn fmt(&self, formatter: &mut Formatter<'_> ) -> Result {
+    fn fmt(&self, formatter: &mut Formatter<'_> ) -> fmt::Result {
         match self {
             Packet::List(ls) => {
                 formatter.write_str("[")?;
@@ -33,152 +37,206 @@ impl<'a> Debug for Packet {
     }
 }
 
-fn parse_line(line: &str) -> Packet {
-    // Assume that the first character is an open brace and start by parsing the second character
-    let mut i = 1;
-    let mut packet_vec = vec!();
-    // Assume that the last character is a closing brace and don't parse it
-    while i < line.len() - 1 {
-        match &line[i..=i] {
-            "[" => {
-                let mut nesting_level = 1;
-                let mut j = i + 1;
-                while nesting_level > 0 {
-                    match &line[j..=j] {
-                        "[" => {
-                            nesting_level += 1;
-                            j += 1;
+impl fmt::Display for Packet {
+    // Writes the packet the way the input does, so it parses back to the same packet
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Number(n) => write!(formatter, "{n}"),
+            Packet::List(ls) => {
+                formatter.write_str("[")?;
+                for (i, packet) in ls.iter().enumerate() {
+                    if i != 0 {
+                        formatter.write_str(",")?;
+                    }
+                    write!(formatter, "{packet}")?;
+                }
+                formatter.write_str("]")
+            },
+        }
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Number(left), Packet::Number(right)) => left.cmp(right),
+            // Lists compare item by item, and the one that runs out first is smaller
+            (Packet::List(left), Packet::List(right)) => left.cmp(right),
+            // A number is compared as if it were a list holding just that number
+            (Packet::Number(_), Packet::List(right)) => std::slice::from_ref(self).cmp(&right[..]),
+            (Packet::List(left), Packet::Number(_)) => left[..].cmp(std::slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Equal in the puzzle's sense, so 2 and [2] are the same packet
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+struct Parser<'a> {
+    line: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(line: &'a str) -> Self {
+        Parser { line, chars: line.char_indices().peekable() }
+    }
+
+    // The next character that isn't whitespace, left in place
+    fn peek(&mut self) -> Option<(usize, char)> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn error(&mut self, expected: &str) -> String {
+        match self.peek() {
+            Some((i, c)) => format!("Expected {expected} at {i} in {:?}, got {c:?}", self.line),
+            None => format!("Expected {expected} at the end of {:?}", self.line),
+        }
+    }
+
+    fn packet(&mut self, depth: usize) -> Result<Packet, String> {
+        match self.peek() {
+            Some((i, '[')) if depth == MAX_DEPTH => {
+                Err(format!("Lists nested more than {MAX_DEPTH} deep at {i} in {:?}", self.line))
+            },
+            Some((_, '[')) => {
+                self.chars.next();
+                let mut packet_vec = vec!();
+                if matches!(self.peek(), Some((_, ']'))) {
+                    self.chars.next();
+                    return Ok(Packet::List(packet_vec));
+                }
+                loop {
+                    packet_vec.push(self.packet(depth + 1)?);
+                    match self.peek() {
+                        Some((_, ',')) => {
+                            self.chars.next();
                         },
-                        "]" => {
-                            nesting_level -= 1;
-                            j += 1;
+                        Some((_, ']')) => {
+                            self.chars.next();
+                            return Ok(Packet::List(packet_vec));
                         },
-                        _ => {
-                            j += 1
-                        }
+                        _ => return Err(self.error("',' or ']'")),
                     }
                 }
-                packet_vec.push(parse_line(&line[i..j]));
-                i = j + 1;
             },
-            _ => {
-                let mut j = i;
-                for c in line[i..line.len()].chars() {
-                    j += 1;
-                    if !c.is_numeric() {
-                        break;
-                    }
+            Some((start, c)) if c == '-' || c.is_ascii_digit() => {
+                self.chars.next();
+                let mut end = start + 1;
+                while let Some((i, _)) = self.chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = i + 1;
                 }
-                packet_vec.push(Packet::Number(line[i..j - 1].parse().unwrap()));
-                i = j;
-            }
+                let n = self.line[start..end].parse()
+                    .map_err(|_| format!("Invalid number {:?} at {start} in {:?}", &self.line[start..end], self.line))?;
+                Ok(Packet::Number(n))
+            },
+            _ => Err(self.error("a packet")),
         }
     }
-    Packet::List(packet_vec)
+}
+
+fn parse_line(line: &str) -> Result<Packet, String> {
+    let mut parser = Parser::new(line);
+    if !matches!(parser.peek(), Some((_, '['))) {
+        return Err(format!("Packets must be lists, got {line:?}"));
+    }
+    let packet = parser.packet(0)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("the end of the packet"));
+    }
+    Ok(packet)
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Packet>, String> {
+    let packets: Vec<Packet> = in_str.trim().lines().filter(|&l| !l.trim().is_empty()).map(parse_line).collect::<Result<_, _>>()?;
+    if !packets.len().is_multiple_of(2) {
+        return Err("Packets must come in pairs".to_string());
+    }
+    Ok(packets)
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day13");
-    let mut parsed: Vec<Packet> = contents.trim().lines().filter(|&l| !l.is_empty()).map(parse_line).collect();
+    let mut parsed = parse_input(&contents).expect("Could not parse input for day13");
     let p1 = part_one(&parsed[..]);
-    parsed.push(Packet::List(vec!(Packet::Number(2))));
-    parsed.push(Packet::List(vec!(Packet::Number(6))));
     let p2 = part_two(&mut parsed[..]);
     println!("Elapsed: {:?}", start.elapsed());
     println!("D13P1: {p1:?}");
     println!("D13P2: {p2:?}");
 }
 
-fn compare_packets(left: &Packet, right: &Packet) -> Ordering {
-    let left = if let Packet::List(ls1) = left { ls1 } else { unreachable!() };
-    let right = if let Packet::List(ls2) = right { ls2 } else { unreachable!() };
-    let (mut i, mut j) = (0, 0);
-    while i < left.len() && j < right.len() {
-        let left = left.get(i).unwrap();
-        let right = right.get(j).unwrap();
-        if let Packet::List(_) = left{
-            if let Packet::List(_) = right {
-                let res = compare_packets(left, right);
-                match res {
-                    Ordering::Equal => {
-                        i += 1;
-                        j += 1;
-                    },
-                    _ => return res,
-                }
-            } else {
-                let r_val = if let Packet::Number(p) = right { p } else { unreachable!() };
-                let res = compare_packets(left, &Packet::List(vec!(Packet::Number(*r_val))));
-                match res {
-                    Ordering::Equal => {
-                        i += 1;
-                        j += 1;
-                    },
-                    _ => return res,
-                }
-            }
-        } else {
-            if let Packet::List(_) = right {
-                let l_val = if let Packet::Number(p) = left { p } else { unreachable!() };
-                let res = compare_packets(&Packet::List(vec!(Packet::Number(*l_val))), right);
-                match res {
-                    Ordering::Equal => {
-                        i += 1;
-                        j += 1;
-                    },
-                    _ => return res,
-                }
-            } else {
-                let l_val = if let Packet::Number(l) = left { l } else { unreachable!() };
-                let r_val = if let Packet::Number(r) = right { r } else { unreachable!() };
-                // println!("Comparing {l_val:?} and {r_val:?}");
-                let res = l_val.cmp(r_val);
-                match res {
-                    Ordering::Equal => {
-                        i += 1;
-                        j += 1;
-                    },
-                    _ => return res,
-                }
-            }
-        }
-    }
-    if i >= left.len() && j >= right.len() {
-        return Ordering::Equal;
-    }
-    if i >= left.len() {
-        return Ordering::Less;
-    }
-    return Ordering::Greater
-
-}
-
-fn part_two(packet_pairs: &mut [Packet]) -> i32 {
-    packet_pairs.sort_by(compare_packets);
-    let (mut i1, mut i2) = (0, 0);
-    for (i, p) in packet_pairs.iter().enumerate() {
-        if let Packet::List(ls) = p {
-            if ls.len() == 1 {
-                if let Packet::Number(val) = ls.get(0).unwrap() {
-                    if *val == 6 {
-                        i1 = i + 1;
-                    } else if *val == 2 {
-                        i2 = i + 1;
-                    }
-                }
-            }
-        }
-    }
-    (i1 * i2) as i32
+fn part_two(packets: &mut [Packet]) -> usize {
+    packets.sort();
+    let dividers = [2, 6].map(|n| Packet::List(vec!(Packet::List(vec!(Packet::Number(n))))));
+    // Binary search for where each divider would go, the second also has the first ahead of it
+    let position = |divider: &Packet| packets.partition_point(|packet| packet < divider);
+    (position(&dividers[0]) + 1) * (position(&dividers[1]) + 2)
 }
 
-fn part_one(packet_pairs: &[Packet]) -> i32 {
+fn part_one(packet_pairs: &[Packet]) -> usize {
     packet_pairs
         .chunks(2)
         .enumerate()
-        .map(|(i, chunk)| (i, compare_packets(&chunk[0], &chunk[1])))
-        .filter(|(_, o)| *o == Ordering::Less)
-        .map(|(i, _)| (i + 1) as i32)
+        .filter(|(_, chunk)| chunk[0] < chunk[1])
+        .map(|(i, _)| i + 1)
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_sample() {
+        let mut packets = parse_input(include_str!("../inputs/day13_input_test.txt")).unwrap();
+        assert_eq!(part_one(&packets), 13);
+        assert_eq!(part_two(&mut packets), 140);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for line in include_str!("../inputs/day13_input_test.txt").lines().filter(|l| !l.is_empty()) {
+            assert_eq!(parse_line(line).unwrap().to_string(), line);
+        }
+        assert_eq!(parse_line(" [ 1 , [-2, []],3 ] ").unwrap().to_string(), "[1,[-2,[]],3]");
+        for (line, expected) in [("[ ]", "[]"), ("[[ ]]", "[[]]"), ("[1, [ ] ]", "[1,[]]"), ("[\t[ [ ] ] , 2 ]", "[[[]],2]")] {
+            assert_eq!(parse_line(line).unwrap().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn mixed_comparisons() {
+        let packet = |line| parse_line(line).unwrap();
+        assert_eq!(packet("[[2]]"), packet("[2]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[-1]") < packet("[0]"));
+        assert!(packet("[[]]") > packet("[]"));
+    }
+
+    #[test]
+    fn invalid_packets() {
+        for line in ["", "1", "[1,]", "[1 2]", "[1]]", "[-]", "[99999999999]", "[1,[2]", "[a]", "[ ,]"] {
+            assert!(parse_line(line).is_err(), "{line}");
+        }
+        assert!(parse_input("[1]").is_err());
+        let deep = "[".repeat(300_000);
+        assert!(parse_input(&format!("{deep}\n{deep}")).is_err());
+        let deepest = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert_eq!(parse_line(&deepest).map(|p| p.to_string()), Ok(deepest));
+    }
+}
@@ -1,65 +1,96 @@
+use std::env;
 use std::fs;
 use std::cmp::{min, max};
 use std::time::Instant;
 
 const FILE_PATH: &str = "inputs/day14_input.txt";
-const SAND_ORIGIN: (u32, u32) = (500, 0);
-const CAVE_WIDTH: usize = 500;
-const CAVE_HEIGHT: usize = 200;
-const X_OFFSET: usize = 250;
-
+const SAND_ORIGIN: (i32, i32) = (500, 0);
+// So a stray coordinate can't ask for more memory than any real cave needs
+const MAX_CAVE_AREA: usize = 1 << 28;
 
 #[derive(Debug)]
-struct Cave {
-    bottom: u32,
-    occupied: [bool; CAVE_HEIGHT * CAVE_WIDTH],
-    drop_cache: Vec<(u32,u32)>,
+pub(crate) struct Cave {
+    bottom: i32,
+    // Each straight piece of the rock paths, as inclusive x and y ranges
+    rocks: Vec<((i32, i32), (i32, i32))>,
 }
 
-fn get_flat_index(x: u32, y: u32) -> usize{
-    y as usize * CAVE_WIDTH + (x as usize - X_OFFSET) % CAVE_WIDTH
+// Every square the sand could reach, for dropping it grain by grain
+#[derive(Debug)]
+struct Grid {
+    bottom: i32,
+    // The leftmost x the grid holds and how many columns it has
+    min_x: i32,
+    width: usize,
+    occupied: Vec<bool>,
+    drop_cache: Vec<(i32, i32)>,
 }
 
-fn parse_input<'a>(lines: impl Iterator<Item = &'a str>) -> Cave {
-    let mut out_cave = Cave { 
-        bottom: 0,
-        occupied: [false; CAVE_HEIGHT * CAVE_WIDTH],
-        drop_cache: vec!(),
-    };
-    for line in lines {
-        let mut points = line.split(" -> ").map(|p|{
-            let (x, y) = p.split_once(',').unwrap();
-            (x.parse::<u32>().unwrap(), y.parse::<u32>().unwrap())
-        });
-        let mut last = points.next().unwrap();
-        out_cave.bottom = max(out_cave.bottom, last.1);
-        while let Some(p) = points.next() {
-            let (x1, y1) = last;
-            let (x2, y2) = p;
-
-            out_cave.bottom = max(out_cave.bottom, y2);
-
-            for y in min(y1,y2)..=max(y1,y2) {
-                out_cave.occupied[get_flat_index(x1, y)] = true;
-            }
-
-            for x in min(x1, x2)..=max(x1,x2) {
-                out_cave.occupied[get_flat_index(x, y1)] = true;
+pub(crate) fn parse_input(in_str: &str) -> Result<Cave, String> {
+    let mut cave = Cave { bottom: SAND_ORIGIN.1, rocks: vec!() };
+    for line in in_str.lines() {
+        let points = line.split(" -> ").map(|p|{
+            let (x, y) = p.split_once(',').ok_or_else(|| format!("Invalid point {p:?}"))?;
+            let point = x.parse::<i32>().ok().zip(y.parse::<i32>().ok()).ok_or_else(|| format!("Invalid point {p:?}"))?;
+            if point.1 < SAND_ORIGIN.1 {
+                return Err(format!("Point {p:?} is above the sand's origin"));
             }
+            Ok(point)
+        }).collect::<Result<Vec<_>, String>>()?;
+        if points.windows(2).any(|w| w[0].0 != w[1].0 && w[0].1 != w[1].1) {
+            return Err(format!("Rock paths must be horizontal or vertical in {line:?}"));
+        }
 
-            last = p;
+        let (&(x, y), rest) = points.split_first().ok_or("Empty rock path")?;
+        if rest.is_empty() {
+            cave.rocks.push(((x, x), (y, y)));
         }
+        for w in points.windows(2) {
+            let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+            cave.rocks.push(((min(x1, x2), max(x1, x2)), (min(y1, y2), max(y1, y2))));
+        }
+        cave.bottom = points.iter().map(|p| p.1).fold(cave.bottom, max);
     }
 
-    out_cave
+    Ok(cave)
 }
 
-impl Cave {
-    fn is_occupied(&self, x: u32, y: u32) -> bool{
-        self.occupied[get_flat_index(x, y)]
+impl Grid {
+    fn new(cave: &Cave) -> Result<Self, String> {
+        // Sand moves at most one column per row, so by the floor two below the lowest rock it has
+        // spread no further than the floor's depth either side of the origin
+        let floor = cave.bottom as i64 + 2;
+        let min_x = cave.rocks.iter().map(|r| r.0.0 as i64).fold(SAND_ORIGIN.0 as i64 - floor, i64::min) - 1;
+        let max_x = cave.rocks.iter().map(|r| r.0.1 as i64).fold(SAND_ORIGIN.0 as i64 + floor, i64::max) + 1;
+        let min_x = i32::try_from(min_x).map_err(|_| "The cave is too wide".to_string())?;
+        let width = (max_x - min_x as i64 + 1) as usize;
+        let area = width.checked_mul(floor as usize + 1).filter(|&area| area <= MAX_CAVE_AREA).ok_or("The cave is too big")?;
+
+        let mut grid = Grid { bottom: cave.bottom, min_x, width, occupied: vec![false; area], drop_cache: vec!() };
+        for &((x1, x2), (y1, y2)) in cave.rocks.iter() {
+            for y in y1..=y2 {
+                for x in x1..=x2 {
+                    grid.occupy(x, y);
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    fn get_flat_index(&self, x: i32, y: i32) -> usize {
+        y as usize * self.width + (x - self.min_x) as usize
     }
 
-    fn drop(&mut self, x:u32, y:u32) -> Result<(u32, u32), ()> {
+    fn is_occupied(&self, x: i32, y: i32) -> bool{
+        self.occupied[self.get_flat_index(x, y)]
+    }
+
+    fn occupy(&mut self, x: i32, y: i32) {
+        let index = self.get_flat_index(x, y);
+        self.occupied[index] = true;
+    }
+
+    fn drop(&mut self, x: i32, y: i32) -> Result<(i32, i32), ()> {
         let (mut x1, mut y1) = (x, y);
         while y1 < self.bottom {
             if !self.is_occupied(x1, y1 + 1) {
@@ -79,7 +110,7 @@ impl Cave {
                 self.drop_cache.push((x1,y1));
                 continue;
             }
-            self.occupied[get_flat_index(x1, y1)] = true;
+            self.occupy(x1, y1);
             self.drop_cache.pop();
             return Ok((x1,y1));
         }
@@ -90,34 +121,54 @@ impl Cave {
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day14");
-    let mut cave = parse_input(contents.lines());
+    let cave = parse_input(&contents).expect("Could not parse input for day14");
 
-    let p1 = part_one(&mut cave);
-    let p2 = part_two(&mut cave) + p1;
+    // Part one needs every grain dropped, so the analytic run leaves it out and never builds the
+    // grid, which lets it take caves far too big for one
+    if env::args().any(|arg| arg == "--analytic") {
+        let p2 = part_two_analytic(&cave);
+        println!("Elapsed: {:?}", start.elapsed());
+        println!("D14P2: {p2:?}");
+        return;
+    }
+    let mut grid = Grid::new(&cave).expect("Could not fit the cave for day14");
+    let p1 = part_one(&mut grid);
+    let p2 = part_two(&mut grid) + p1;
     println!("Elapsed: {:?}", start.elapsed());
     println!("D14P1: {p1:?}");
     println!("D14P2: {p2:?}");
 }
 
-fn part_one(cave: &mut Cave) -> u32 {
+fn part_one(cave: &mut Grid) -> u32 {
     let mut settled_count = 0;
+    if cave.is_occupied(SAND_ORIGIN.0, SAND_ORIGIN.1) {
+        return 0;
+    }
     let  (mut x, mut y) = SAND_ORIGIN;
-    while let Ok(_) = cave.drop(x, y) {
+    while let Ok(settled) = cave.drop(x, y) {
+        settled_count += 1;
+        if settled == SAND_ORIGIN {
+            break;
+        }
         (x,y) = if let Some(cache_drop) = cave.drop_cache.pop() { 
             cache_drop
         } else { 
             SAND_ORIGIN 
         };
-        settled_count += 1;
     }
     settled_count
 }
 
-fn part_two(cave: &mut Cave) -> u32 {
+fn part_two(cave: &mut Grid) -> u32 {
     let mut settled_count = 0;
-    let last_row_index = CAVE_WIDTH * ((cave.bottom + 2) as usize) - 1;
-    cave.occupied[last_row_index..last_row_index + CAVE_WIDTH].iter_mut().for_each(|x| *x = true);
-    cave.bottom = cave.bottom + 2;
+    let floor = cave.bottom + 2;
+    for x in cave.min_x..cave.min_x + cave.width as i32 {
+        cave.occupy(x, floor);
+    }
+    cave.bottom = floor;
+    if cave.is_occupied(SAND_ORIGIN.0, SAND_ORIGIN.1) {
+        return 0;
+    }
     let res = cave.drop_cache.pop();
     let (mut x, mut y) = if let Some(drop) = res { drop } else { SAND_ORIGIN };
     while let Ok(settled) = cave.drop(x, y) {
@@ -133,3 +184,161 @@ fn part_two(cave: &mut Cave) -> u32 {
     }
     settled_count
 }
+
+// Sand comes to rest on a square when it isn't rock and sand rests on the square above it or on
+// either side of that one, so the whole pile can be worked out a row at a time. Each row is kept
+// as runs of sand, which makes a row cost as much as the rock on it rather than its width, and
+// rows without rock are skipped over in one go
+fn part_two_analytic(cave: &Cave) -> u64 {
+    let floor = cave.bottom as i64 + 2;
+    let mut rocks: Vec<(i64, i64, i64, i64)> = cave.rocks.iter()
+        .map(|&((x1, x2), (y1, y2))| (y1 as i64, y2 as i64, x1 as i64, x2 as i64))
+        .collect();
+    rocks.sort_unstable();
+    let mut pending = rocks.into_iter().peekable();
+    let mut active = vec!();
+
+    let mut sand = vec![(SAND_ORIGIN.0 as i64, SAND_ORIGIN.0 as i64)];
+    let mut y = SAND_ORIGIN.1 as i64;
+    let mut settled_count = 0;
+    loop {
+        while let Some(rock) = pending.next_if(|r| r.0 <= y) {
+            active.push(rock);
+        }
+        active.retain(|r| r.1 >= y);
+        sand = subtract_runs(&sand, &merge_runs(active.iter().map(|r| (r.2, r.3)).collect()));
+        settled_count += run_widths(&sand);
+        if sand.is_empty() {
+            break;
+        }
+
+        let next_rock = if active.iter().any(|r| r.1 > y) { y + 1 } else { pending.peek().map_or(floor, |r| r.0) };
+        let mut open_rows = min(next_rock, floor) - y - 1;
+        while open_rows > 0 {
+            // Until two runs meet, every run just grows by one each side per row
+            let rows = sand.windows(2).map(|w| (w[1].0 - w[0].1) / 2 - 1).fold(open_rows, min);
+            if rows > 0 {
+                let (count, width) = (sand.len() as i128, run_widths(&sand) as i128);
+                let rows_128 = rows as i128;
+                settled_count += (rows_128 * width + count * rows_128 * (rows_128 + 1)) as u64;
+                sand.iter_mut().for_each(|run| *run = (run.0 - rows, run.1 + rows));
+                open_rows -= rows;
+            } else {
+                sand = spread_runs(&sand);
+                settled_count += run_widths(&sand);
+                open_rows -= 1;
+            }
+        }
+        if next_rock >= floor {
+            break;
+        }
+        sand = spread_runs(&sand);
+        y = next_rock;
+    }
+    settled_count
+}
+
+// The squares sand can reach from the runs on the row above
+fn spread_runs(runs: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    merge_runs(runs.iter().map(|&(from, to)| (from - 1, to + 1)).collect())
+}
+
+fn run_widths(runs: &[(i64, i64)]) -> u64 {
+    runs.iter().map(|(from, to)| (to - from + 1) as u64).sum()
+}
+
+// Sorts runs and joins any that overlap or touch
+fn merge_runs(mut runs: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    runs.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = vec!();
+    for (from, to) in runs {
+        match merged.last_mut() {
+            Some(last) if from <= last.1 + 1 => last.1 = max(last.1, to),
+            _ => merged.push((from, to)),
+        }
+    }
+    merged
+}
+
+// Both sets of runs must be sorted and separate
+fn subtract_runs(runs: &[(i64, i64)], holes: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut out = vec!();
+    let mut holes = holes.iter().peekable();
+    for &(mut from, to) in runs {
+        while let Some(&&(hole_from, hole_to)) = holes.peek() {
+            if hole_to < from {
+                holes.next();
+                continue;
+            }
+            if hole_from > to {
+                break;
+            }
+            if hole_from > from {
+                out.push((from, hole_from - 1));
+            }
+            from = hole_to + 1;
+            if hole_to > to {
+                break;
+            }
+            holes.next();
+        }
+        if from <= to {
+            out.push((from, to));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn rock_path() -> impl Strategy<Value = String> {
+        (480..520i32, 1..30i32, prop::collection::vec((any::<bool>(), -6..=6i32), 1..5)).prop_map(|(mut x, mut y, moves)| {
+            let mut points = vec!(format!("{x},{y}"));
+            for (horizontal, length) in moves {
+                if horizontal { x += length } else { y = (y + length).max(0) }
+                points.push(format!("{x},{y}"));
+            }
+            points.join(" -> ")
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn analytic_matches_simulation(paths in prop::collection::vec(rock_path(), 1..12)) {
+            let cave = parse_input(&paths.join("\n")).unwrap();
+            let mut grid = Grid::new(&cave).unwrap();
+            let p1 = part_one(&mut grid);
+            prop_assert_eq!(part_two_analytic(&cave), (part_two(&mut grid) + p1) as u64);
+        }
+    }
+
+    #[test]
+    fn caves_of_any_size() {
+        let cave = parse_input(include_str!("../inputs/day14_input_test.txt")).unwrap();
+        assert_eq!(part_two_analytic(&cave), 93);
+        let mut grid = Grid::new(&cave).unwrap();
+        assert_eq!(part_one(&mut grid), 24);
+        assert_eq!(part_two(&mut grid) + 24, 93);
+
+        // Far outside the old fixed 500x200 cave, and deep enough that the sand spreads past x = 0
+        let cave = parse_input("-40,600 -> -30,600").unwrap();
+        let mut grid = Grid::new(&cave).unwrap();
+        assert_eq!(part_one(&mut grid), 0);
+        // The whole triangle down to the floor, less the rock and the 9 cells sheltered under it
+        assert_eq!(part_two_analytic(&cave), 602 * 602 - 11 - 9);
+        assert_eq!(part_two(&mut grid), 602 * 602 - 11 - 9);
+
+        assert!(parse_input("500,-1 -> 500,3").is_err());
+        // Far too big to simulate, but nothing sits under the rock so it shelters nothing
+        let cave = parse_input("500,2000000000 -> 501,2000000000").unwrap();
+        assert!(Grid::new(&cave).is_err());
+        assert_eq!(part_two_analytic(&cave), 2_000_000_002u64.pow(2) - 2);
+        // Rock blocking the origin, and a wall running down most of the cave
+        assert_eq!(part_two_analytic(&parse_input("499,0 -> 501,0").unwrap()), 0);
+        let cave = parse_input("498,3 -> 498,1000\n470,20 -> 480,20").unwrap();
+        assert_eq!(part_two_analytic(&cave), part_two(&mut Grid::new(&cave).unwrap()) as u64);
+    }
+}
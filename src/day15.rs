@@ -1,3 +1,6 @@
+mod coverage;
+
+use std::env;
 use std::fs;
 use std::time::Instant;
 use std::cmp::{min, max};
@@ -5,9 +8,11 @@ use std::cmp::{min, max};
 const FILE_PATH: &str = "inputs/day15_input.txt";
 const SCAN_LINE: i32 = 2000000;
 const DISTRESS_UPPER_BOUND: i32 = 4_000_000;
-const DISTRESS_LOWER_BOUND: i32 = 0;
+const TUNING_MULTIPLIER: i64 = 4_000_000;
+// Real inputs span millions of points each way, far too many to draw as text
+const MAX_ASCII_CELLS: i64 = 1 << 20;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Point(i32, i32);
 
 impl Point {
@@ -16,7 +21,8 @@ impl Point {
     }
 }
 
-struct Sensor {
+#[derive(Debug)]
+pub(crate) struct Sensor {
     beacon: Point,
     location: Point,
     beacon_distance: i32,
@@ -45,10 +51,6 @@ impl ScanRange {
             other.max >= self.min && other.max <= self.max ||
             other.min >= self.min && other.min <= self.max
     }
-    fn contains(&self, val: i32) -> bool {
-        self.min <= val && self.max >= val
-    }
-
 }
 
 #[derive(Debug)]
@@ -93,10 +95,6 @@ impl Scanned {
         self.ranges.push(rng1);
     }
 
-    fn contains(&self, val: i32) -> bool {
-        self.ranges.iter().any(|rng| rng.contains(val))
-    }
-
     fn remove(&mut self, v: i32) {
         for i in 0..self.ranges.len() {
             let rng = &mut self.ranges[i];
@@ -132,37 +130,69 @@ impl Scanned {
     }
 }
 
-fn parse_input(input: &str) -> Vec<Sensor> {
+// Keeps every distance between two points within an i32
+const COORDINATE_LIMIT: u32 = i32::MAX as u32 / 4;
+
+fn parse_point(in_str: &str) -> Result<Point, String> {
+    let (x, y) = in_str.strip_prefix("x=").and_then(|p| p.split_once(", y="))
+        .ok_or_else(|| format!("Invalid position {in_str:?}"))?;
+    let point = x.parse().ok().zip(y.parse().ok()).map(|(x, y)| Point(x, y))
+        .ok_or_else(|| format!("Invalid position {in_str:?}"))?;
+    if point.0.unsigned_abs() > COORDINATE_LIMIT || point.1.unsigned_abs() > COORDINATE_LIMIT {
+        return Err(format!("Position {in_str:?} is too far away"));
+    }
+    Ok(point)
+}
+
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Sensor>, String> {
     let mut sensors = vec!();
     for line in input.lines() {
-        let (sensor_str, beacon_str) = line.split_once(": ").unwrap();
-        let (_, x_str) = sensor_str.split_once("x=").unwrap();
-        let (sensor_x, sensor_y) = x_str.split_once(", y=").unwrap();
-        let (_, x_str) = beacon_str.split_once("x=").unwrap();
-        let (beacon_x, beacon_y) = x_str.split_once(", y=").unwrap();
-
-        sensors.push(Sensor::new(
-            Point(sensor_x.parse().unwrap(), sensor_y.parse().unwrap()),
-            Point(beacon_x.parse().unwrap(), beacon_y.parse().unwrap()),
-        ));
-    }
-    sensors
+        let (sensor_str, beacon_str) = line.strip_prefix("Sensor at ")
+            .and_then(|l| l.split_once(": closest beacon is at "))
+            .ok_or_else(|| format!("Invalid sensor {line:?}"))?;
+
+        sensors.push(Sensor::new(parse_point(sensor_str)?, parse_point(beacon_str)?));
+    }
+    Ok(sensors)
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day15");
-    let sensors = parse_input(&contents);
+    let sensors = parse_input(&contents).expect("Could not parse input for day15");
     let mut scanned = Scanned::new();
-    part_one(&sensors, SCAN_LINE, false, &mut scanned);
+    part_one(&sensors, SCAN_LINE, &mut scanned);
     let p1 = scanned.size();
-    let p2 = part_two(&sensors, &mut scanned);
+    let args: Vec<String> = env::args().collect();
+    let flag_value = |flag: &str| args.iter().position(|arg| arg == flag).map(|i| {
+        args.get(i + 1).unwrap_or_else(|| panic!("{flag} needs a value"))
+    });
+    let bound = flag_value("--bound").map_or(DISTRESS_UPPER_BOUND, |b| b.parse().expect("--bound must be a number"));
+    let distress = part_two(&sensors, bound);
+    let p2 = distress.map_or("no gap".to_string(), |Point(x, y)| (x as i64 * TUNING_MULTIPLIER + y as i64).to_string());
     println!("Elapsed: {:?}", start.elapsed());
+
+    // --ascii draws everything around the sensors and beacons, --ppm <file> the whole search area
+    if args.iter().any(|arg| arg == "--ascii") {
+        let points = || sensors.iter().flat_map(|s| [s.location, s.beacon]);
+        let xs = points().map(|p| p.0).min().unwrap_or(0)..=points().map(|p| p.0).max().unwrap_or(0);
+        let ys = points().map(|p| p.1).min().unwrap_or(0)..=points().map(|p| p.1).max().unwrap_or(0);
+        let (width, height) = (*xs.end() as i64 - *xs.start() as i64 + 1, *ys.end() as i64 - *ys.start() as i64 + 1);
+        if width * height > MAX_ASCII_CELLS {
+            eprintln!("{width} by {height} is too big to draw as text, use --ppm <file> instead");
+        } else {
+            println!("{}", coverage::render_ascii(&sensors, &sensors, xs, ys, distress));
+        }
+    }
+    if let Some(path) = flag_value("--ppm") {
+        let size = flag_value("--size").map_or(1000, |s| s.parse().expect("--size must be a number"));
+        fs::write(path, coverage::render_ppm(&sensors, bound, size, distress)).expect("Could not write the coverage image");
+    }
     println!("D15P1: {p1:?}");
-    println!("D15P2: {p2:?}");
+    println!("D15P2: {p2}");
 }
 
-fn part_one(sensors: &Vec<Sensor>, line: i32, set_bounds: bool, scanned: &mut Scanned) {
+fn part_one(sensors: &[Sensor], line: i32, scanned: &mut Scanned) {
     for sensor in sensors {
         let Point(x, _) = sensor.location;
         let line_distance = sensor.location.get_distance(Point(x, line));
@@ -170,35 +200,103 @@ fn part_one(sensors: &Vec<Sensor>, line: i32, set_bounds: bool, scanned: &mut Sc
         if intersection < 0 {
             continue
         }
-        if set_bounds {
-            let min_bound = max(x - intersection, DISTRESS_LOWER_BOUND);
-            let max_bound = min(x + intersection, DISTRESS_UPPER_BOUND);
-            scanned.insert(ScanRange::new(min_bound, max_bound));
-        } else {
-            scanned.insert(ScanRange::new(x-intersection, x + intersection));
-        }
+        scanned.insert(ScanRange::new(x-intersection, x + intersection));
     }
     
-    if !set_bounds {
-        for Sensor{ beacon, .. } in sensors {
-            if beacon.1 == line {
-                scanned.remove(beacon.0);
+    for Sensor{ beacon, .. } in sensors {
+        if beacon.1 == line {
+            scanned.remove(beacon.0);
+        }
+    }
+}
+
+// Finds a point in the square from 0 to `bound` that no sensor covers. Turned 45 degrees, with
+// u = x + y and v = x - y, each sensor covers a square. The uncovered point with the largest x + y
+// is either the search area's far corner or sits on a line just outside some sensor's square, so
+// only those lines need searching.
+fn part_two(sensors: &[Sensor], bound: i32) -> Option<Point> {
+    let bound = bound as i64;
+    let squares: Vec<(i64, i64, i64)> = sensors.iter()
+        .map(|sensor| {
+            let Point(x, y) = sensor.location;
+            (x as i64 + y as i64, x as i64 - y as i64, sensor.beacon_distance as i64)
+        })
+        .collect();
+    let uncovered = |x: i64, y: i64| {
+        (0..=bound).contains(&x) && (0..=bound).contains(&y)
+            && squares.iter().all(|&(u, v, reach)| (x + y - u).abs().max((x - y - v).abs()) > reach)
+    };
+    if uncovered(bound, bound) {
+        return Some(Point(bound as i32, bound as i32));
+    }
+
+    for &(u, v, reach) in &squares {
+        for line in [u - reach - 1, u + reach + 1] {
+            // Along u = line, v runs between these while x and y stay in the search area
+            let covered = squares.iter().filter(|s| (line - s.0).abs() <= s.2).map(|s| (s.1 - s.2, s.1 + s.2)).collect();
+            if let Some(v) = first_gap(max(-line, line - 2 * bound), min(2 * bound - line, line), covered) {
+                return Some(Point(((line + v) / 2) as i32, ((line - v) / 2) as i32));
+            }
+        }
+        for line in [v - reach - 1, v + reach + 1] {
+            let covered = squares.iter().filter(|s| (line - s.1).abs() <= s.2).map(|s| (s.0 - s.2, s.0 + s.2)).collect();
+            if let Some(u) = first_gap(max(-line, line), min(2 * bound - line, 2 * bound + line), covered) {
+                return Some(Point(((u + line) / 2) as i32, ((u - line) / 2) as i32));
             }
         }
     }
+    None
 }
 
-fn part_two(sensors: &Vec<Sensor>, scanned: &mut Scanned) -> u128 {
-    for line in 0..DISTRESS_UPPER_BOUND {
-        scanned.ranges.clear();
-        part_one(sensors, line, true, scanned);
-        if scanned.size() == DISTRESS_UPPER_BOUND as usize {
-            for x in 0..DISTRESS_UPPER_BOUND {
-                if !scanned.contains(x) {
-                    return ((x as u128 * 4_000_000 as u128) + line as u128) as u128;
-                }
+// The first value from `from` to `to` that no range in `covered` holds. Only every other value is
+// a whole point on a diagonal, so it steps by two
+fn first_gap(from: i64, to: i64, mut covered: Vec<(i64, i64)>) -> Option<i64> {
+    covered.sort_unstable();
+    let mut next = from;
+    for (low, high) in covered {
+        if low > next {
+            break;
+        }
+        if high >= next {
+            next = high + 1 + (high + 1 - from).rem_euclid(2);
+        }
+    }
+    (next <= to).then_some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn uncovered(sensors: &[Sensor], bound: i32) -> Vec<(i32, i32)> {
+        (0..=bound)
+            .flat_map(|x| (0..=bound).map(move |y| (x, y)))
+            .filter(|&(x, y)| sensors.iter().all(|s| s.location.get_distance(Point(x, y)) > s.beacon_distance))
+            .collect()
+    }
+
+    #[test]
+    fn sample_distress_beacon() {
+        let sensors = parse_input(include_str!("../inputs/day15_input_test.txt")).unwrap();
+        let Point(x, y) = part_two(&sensors, 20).unwrap();
+        assert_eq!(x as i64 * TUNING_MULTIPLIER + y as i64, 56000011);
+        assert!(part_two(&sensors, 10).is_none());
+        assert_eq!(uncovered(&sensors, 10), []);
+    }
+
+    fn sensor() -> impl Strategy<Value = Sensor> {
+        (-5..35, -5..35, -8..=8, -8..=8).prop_map(|(x, y, dx, dy)| Sensor::new(Point(x, y), Point(x + dx, y + dy)))
+    }
+
+    proptest! {
+        #[test]
+        fn finds_a_gap_whenever_there_is_one(sensors in prop::collection::vec(sensor(), 1..12), bound in 0..30) {
+            let gaps = uncovered(&sensors, bound);
+            match part_two(&sensors, bound) {
+                Some(Point(x, y)) => prop_assert!(gaps.contains(&(x, y))),
+                None => prop_assert!(gaps.is_empty()),
             }
         }
     }
-    0
 }
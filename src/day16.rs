@@ -1,186 +1,380 @@
 #![feature(iter_collect_into)]
 
+use std::env;
 use std::fs;
 use std::time::Instant;
-use std::cmp::max;
-use std::collections::VecDeque;
 
-use bitmaps::Bitmap;
-use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
 use regex::Regex;
 use lazy_static::lazy_static;
 
 const FILE_PATH: &str = "inputs/day16_input.txt";
 const START_VALVE: &str = "AA";
+// Opened valves, one bit for each valve with a flow rate
+type ValveSet = u64;
+const MAX_RELEVANT_VALVES: usize = ValveSet::BITS as usize;
 lazy_static! {
     static ref VALVE_REGEX: Regex = Regex::new(r"Valve (?P<valve_name>[A-Z]{2}) has flow rate=(?P<flow_rate>\d+); tunnels? leads? to valves? (?P<paths>([A-Z]{2}(, )?)+)")
         .unwrap();
 }
 
-struct Valves<'a> {
-    flow_rates: FxHashMap<&'a str, i32>,
-    hops: FxHashMap<(&'a str, &'a str), i32>,
+// Far enough that no valve behind it can be opened in time, small enough to add to
+const UNREACHABLE: i32 = i32::MAX / 2;
+
+pub(crate) struct Valves<'a> {
+    names: Vec<&'a str>,
+    flow_rates: Vec<i32>,
+    // Minutes to walk between every pair of valves
+    distances: Vec<Vec<i32>>,
+    start: usize,
+    // The valves worth opening, bit i of a ValveSet stands for relevant[i]
+    relevant: Vec<usize>,
 }
 
 impl<'a> Valves<'a> {
-    fn new(valves: impl Iterator<Item = (&'a str, i32, Vec<&'a str>)>) -> Self {
-        let mut flow_rates = FxHashMap::default(); 
-        let mut adjacencies = FxHashMap::default(); 
-        let mut hops = FxHashMap::default();
-
-        valves.for_each(|(n, f, adj)| {
-            flow_rates.insert(n, f);
-            adjacencies.insert(n, adj);
-        });
-
+    pub(crate) fn new(scans: impl Iterator<Item = ValveScan<'a>>) -> Self {
+        let scans: Vec<ValveScan> = scans.collect();
+        let names: Vec<&str> = scans.iter().map(|&(name, _, _)| name).collect();
+        let index: FxHashMap<&str, usize> = names.iter().enumerate().map(|(i, &name)| (name, i)).collect();
 
-        for &v1 in adjacencies.keys() {
-            'inner : for &v2 in adjacencies.keys() {
-                if hops.contains_key(&(v1, v2)) {
-                    continue;
-                }
-                let mut queue = VecDeque::new();
-                let mut visited = FxHashSet::default();
-                queue.push_back((0, v1));
-                visited.insert(v1);
-                while let Some((n, valve)) = queue.pop_front() {
-                    for &v in adjacencies.get(valve).unwrap() {
-                        if visited.contains(v) {
-                            continue;
-                        }
-                        hops.insert((v1, v), n + 1);
-                        hops.insert((v, v1), n + 1);
-                        if v == v2 {
-                            continue 'inner;
-                        }
-                        queue.push_back((n + 1, v));
-                        visited.insert(v);
-                    }
+        // Floyd–Warshall, starting from the tunnels
+        let n = names.len();
+        let mut distances = vec![vec![UNREACHABLE; n]; n];
+        for (i, (_, _, tunnels)) in scans.iter().enumerate() {
+            distances[i][i] = 0;
+            for tunnel in tunnels {
+                distances[i][index[tunnel]] = 1;
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    distances[i][j] = distances[i][j].min(distances[i][k] + distances[k][j]);
                 }
             }
         }
 
-
-
-        Valves { 
-            flow_rates,
-            hops,
+        Valves {
+            flow_rates: scans.iter().map(|&(_, flow_rate, _)| flow_rate).collect(),
+            relevant: (0..n).filter(|&i| scans[i].1 > 0).collect(),
+            start: index[START_VALVE],
+            names,
+            distances,
         }
     }
+}
 
-    fn search(&self, v1: &'a str, v2: &'a str) -> i32 {
-        if self.hops.contains_key(&(v1, v2)) {
-            return self.hops[&(v1, v2)]
-        }
-        panic!()
-    }
+// A valve and the minute it gets opened in, counting from 1 like the puzzle does
+#[derive(Debug, Clone, Copy)]
+struct Opening {
+    valve: usize,
+    minute: i32,
 }
 
-fn parse_line<'a>(in_str: &'a str) -> (&'a str, i32, Vec<&'a str>) {
-    let captures = VALVE_REGEX.captures(in_str).unwrap();
+pub(crate) struct Plan {
+    pressure: i32,
+    schedules: Vec<Vec<Opening>>,
+}
+
+type ValveScan<'a> = (&'a str, i32, Vec<&'a str>);
+
+fn parse_line(in_str: &str) -> Result<ValveScan<'_>, String> {
+    let captures = VALVE_REGEX.captures(in_str).ok_or_else(|| format!("Invalid scan {in_str:?}"))?;
     let name = &in_str[captures.name("valve_name").unwrap().range()];
-    let flow_rate = in_str[captures.name("flow_rate").unwrap().range()].parse().unwrap();
+    let flow_rate = in_str[captures.name("flow_rate").unwrap().range()].parse()
+        .map_err(|_| format!("Invalid flow rate in {in_str:?}"))?;
     let paths = in_str[captures.name("paths").unwrap().range()].split(", ").collect();
 
-    (name, flow_rate, paths)
+    Ok((name, flow_rate, paths))
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<ValveScan<'_>>, String> {
+    let scans = in_str.lines().map(parse_line).collect::<Result<Vec<_>, _>>()?;
+    let names: FxHashSet<&str> = scans.iter().map(|&(name, _, _)| name).collect();
+    if !names.contains(START_VALVE) {
+        return Err(format!("There is no valve {START_VALVE}"));
+    }
+    if let Some(path) = scans.iter().flat_map(|(_, _, paths)| paths).find(|&path| !names.contains(path)) {
+        return Err(format!("Tunnel to unknown valve {path}"));
+    }
+    if scans.iter().filter(|&&(_, flow_rate, _)| flow_rate > 0).count() > MAX_RELEVANT_VALVES {
+        return Err(format!("At most {MAX_RELEVANT_VALVES} valves can have a flow rate"));
+    }
+    Ok(scans)
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day16");
-    let mut valves = Valves::new(contents.lines().map(parse_line)); 
-    let relevant_valves: Vec<&str> = valves.flow_rates
-        .iter()
-        .filter_map(|(&n, &f)| 
-            if f > 0 { Some(n) } else { None }
-        )
-        .collect();
-
-    let p1 = part_one(&mut valves, &relevant_valves);
-    let p2 = part_two(&mut valves, &relevant_valves);
+    let valves = Valves::new(parse_input(&contents).expect("Could not parse input for day16").into_iter());
+    let args: Vec<String> = env::args().collect();
+    let flag_value = |flag: &str| args.iter().position(|arg| arg == flag).map(|i| {
+        args.get(i + 1).unwrap_or_else(|| panic!("{flag} needs a value"))
+    });
+    // Part two can be asked for any number of agents and minutes
+    let agents = flag_value("--agents").map_or(2, |a| a.parse().expect("--agents must be a number"));
+    let minutes = flag_value("--minutes").map_or(26, |m| m.parse().expect("--minutes must be a number"));
+
+    let p1 = part_one(&valves);
+    let p2 = part_two(&valves, agents, minutes);
     println!("Elapsed: {:?}", start.elapsed());
-    println!("D16P1: {p1:?}");
-    println!("D16P2: {p2:?}");
+    if args.iter().any(|arg| arg == "--schedule") {
+        println!("Part one:\n{}", render_plan(&valves, &p1));
+        println!("Part two:\n{}", render_plan(&valves, &p2));
+    }
+    println!("D16P1: {}", p1.pressure);
+    println!("D16P2: {}", p2.pressure);
 }
 
-fn part_one<'a>(valves: &mut Valves<'a>, relevant_valves: &Vec<&'a str>) -> i32 {
-    compute(valves, relevant_valves, 30, Bitmap::new())
+pub(crate) fn part_one(valves: &Valves) -> Plan {
+    plan(valves, 1, 30)
 }
 
-fn part_two<'a> (valves: &mut Valves<'a>, relevant_valves: &Vec<&'a str>) -> i32 {
-    (1..usize::pow(2, (relevant_valves.len()) as u32) / 2).into_par_iter()
-        .map(|mask| {
-            if mask.count_ones() < 7 || mask.count_ones() > 8 {
-                 return 0
-            }
-            let mut mask = Bitmap::from_value(mask as u16);
-            let r1 = compute(valves, relevant_valves, 26, mask);
-            mask.invert();
-            let r2 = compute(valves, relevant_valves, 26, mask);
-            r1 + r2
+pub(crate) fn part_two(valves: &Valves, agents: usize, minutes: i32) -> Plan {
+    plan(valves, agents, minutes)
+}
+
+fn render_plan(valves: &Valves, plan: &Plan) -> String {
+    let agents = plan.schedules.len();
+    plan.schedules.iter().enumerate()
+        .map(|(i, schedule)| {
+            let actor = match i {
+                0 => "You".to_string(),
+                1 if agents == 2 => "Elephant".to_string(),
+                _ => format!("Elephant {i}"),
+            };
+            let openings: Vec<String> = schedule.iter()
+                .map(|opening| format!("{} at minute {}", valves.names[opening.valve], opening.minute))
+                .collect();
+            format!("  {actor}: {}", if openings.is_empty() { "nothing".to_string() } else { openings.join(", ") })
         })
-        .reduce(|| i32::MIN, max)
-        //.reduce(max).unwrap()
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-fn compute<'a>(valves: &Valves<'a>, relevant_valves: &Vec<&'a str>, starting_time: i32, starting_state: Bitmap<16>) -> i32 {
-    type Memo<'a> = FxHashMap<(i32, &'a str, Bitmap<16>), i32>;
-    let mut memo: Memo = FxHashMap::default();
+// Each agent opens a set of valves, and no two sets can overlap. The agents are interchangeable, so
+// each one picks from the sets at or after the previous agent's, and since the sets go from most
+// pressure down a branch can stop once filling every remaining agent with its next set can't win
+fn plan(valves: &Valves, agents: usize, minutes: i32) -> Plan {
+    let mut sets: Vec<(ValveSet, i32)> = best_per_set(valves, minutes).into_iter().collect();
+    sets.sort_unstable_by_key(|&(_, pressure)| -pressure);
 
-    fn helper<'a>(time_remaining: i32, current_valve: &'a str, all_valves: &Valves<'a>, relevant_valves: &[&'a str], mut states: Bitmap<16>, memo: &mut Memo<'a>) -> i32 {
-        let memo_key = (time_remaining, current_valve, states.clone());
-        if memo.contains_key(&memo_key) {
-            return *memo.get(&memo_key).unwrap();
+    type Choice = (i32, Vec<usize>);
+    fn choose(sets: &[(ValveSet, i32)], agents_left: usize, from: usize, opened: ValveSet, chosen: &mut Choice, best: &mut Choice) {
+        if agents_left == 0 {
+            if chosen.0 > best.0 {
+                *best = chosen.clone();
+            }
+            return;
         }
-        let mask = Bitmap::mask(relevant_valves.len());
-        if time_remaining <= 0 || (states & mask) == mask {
-            // All valves are open, there is nothing more we can do
-            memo.insert(memo_key, 0);
-            return 0;
+        for (i, &(set, pressure)) in sets.iter().enumerate().skip(from) {
+            if chosen.0 + pressure * agents_left as i32 <= best.0 {
+                break;
+            }
+            if opened & set != 0 {
+                continue;
+            }
+            chosen.0 += pressure;
+            chosen.1.push(i);
+            choose(sets, agents_left - 1, i, opened | set, chosen, best);
+            chosen.0 -= pressure;
+            chosen.1.pop();
         }
+    }
 
-        let current_position = relevant_valves.iter().position(|&v| v == current_valve);
-        let should_consider_current = current_position.is_some() && !states.get(current_position.unwrap());
-
-        let time_delta_with_flipping =  if should_consider_current {
-            let current_position = current_position.unwrap();
-            states.set(current_position, true);
-            let current_flow_delta = (time_remaining - 1) * all_valves.flow_rates.get(current_valve).unwrap();
-
-            let max_moving_on = (0..relevant_valves.len())
-                .filter(|&i| !states.get(i))
-                .map(|i| {
-                    let n = relevant_valves[i];
-                    let t_delta = 1 + all_valves.search(current_valve, n);
-                    helper(time_remaining - t_delta, n, all_valves, relevant_valves, states.clone(), memo)
-                })
-                .reduce(max)
-                .unwrap_or_else(||0);
-
-            states.set(current_position, false);
-
-            current_flow_delta + max_moving_on
-        } else {
-            0
-        };
+    // Opening nothing is always a set, so even a plan releasing no pressure beats this
+    let mut best = (-1, vec!());
+    choose(&sets, agents, 0, 0, &mut (0, vec!()), &mut best);
+    Plan {
+        pressure: best.0.max(0),
+        schedules: best.1.into_iter().map(|i| schedule(valves, minutes, sets[i].0)).collect(),
+    }
+}
 
-        let time_delta_without_flipping = {
-            (0..relevant_valves.len())
-                .filter(|&i| !states.get(i) && relevant_valves[i] != current_valve)
-                .map(|i| {
-                    let n = relevant_valves[i];
-                    let t_delta = all_valves.search(current_valve, n);
-                    helper(time_remaining - t_delta, n, all_valves, relevant_valves, states.clone(), memo)
-                })
-                .reduce(max)
-                .unwrap_or_else(||0)
+// The most pressure one actor can release in `minutes` by opening exactly each set of valves it
+// has time to reach
+fn best_per_set(valves: &Valves, minutes: i32) -> FxHashMap<ValveSet, i32> {
+    fn visit(valves: &Valves, current_valve: usize, time_remaining: i32, opened: ValveSet, pressure: i32, best: &mut FxHashMap<ValveSet, i32>) {
+        let entry = best.entry(opened).or_insert(0);
+        *entry = (*entry).max(pressure);
+        for (i, &next) in valves.relevant.iter().enumerate() {
+            if opened & 1 << i != 0 {
+                continue;
+            }
+            // Walk there, then spend a minute opening it
+            let time_left = time_remaining - valves.distances[current_valve][next] - 1;
+            if time_left > 0 {
+                let released = time_left * valves.flow_rates[next];
+                visit(valves, next, time_left, opened | 1 << i, pressure + released, best);
+            }
+        }
+    }
+
+    let mut best = FxHashMap::default();
+    visit(valves, valves.start, minutes, 0, 0, &mut best);
+    best
+}
+
+// The order to open a set of valves in that gets its best pressure. Only the chosen sets need one,
+// so it's searched for again here rather than kept for every set
+fn schedule(valves: &Valves, minutes: i32, set: ValveSet) -> Vec<Opening> {
+    type Best = (i32, Vec<Opening>);
+    fn visit(valves: &Valves, minutes: i32, set: ValveSet, opened: ValveSet, pressure: i32, openings: &mut Vec<Opening>, best: &mut Best) {
+        if opened == set && pressure > best.0 {
+            *best = (pressure, openings.clone());
+        }
+        let (current_valve, time_remaining) = openings.last()
+            .map_or((valves.start, minutes), |opening| (opening.valve, minutes - opening.minute));
+        for (i, &next) in valves.relevant.iter().enumerate() {
+            if opened & 1 << i != 0 || set & 1 << i == 0 {
+                continue;
+            }
+            let time_left = time_remaining - valves.distances[current_valve][next] - 1;
+            if time_left > 0 {
+                let released = time_left * valves.flow_rates[next];
+                openings.push(Opening { valve: next, minute: minutes - time_left });
+                visit(valves, minutes, set, opened | 1 << i, pressure + released, openings, best);
+                openings.pop();
+            }
+        }
+    }
+
+    let mut best = (-1, vec!());
+    visit(valves, minutes, set, 0, 0, &mut vec!(), &mut best);
+    best.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const NAMES: [&str; 6] = ["AA", "BB", "CC", "DD", "EE", "FF"];
+
+    type Scan = Vec<(i32, Vec<usize>)>;
+
+    // Walks the tunnels a minute at a time, trying every move for every actor
+    fn most_pressure(scan: &Scan, minutes: usize, actors: usize) -> i32 {
+        let n = scan.len();
+        let states = n.pow(actors as u32) << n;
+        let mut best = vec![0; states];
+        for time_remaining in 1..=minutes as i32 {
+            let mut next = vec![0; states];
+            for (state, result) in next.iter_mut().enumerate() {
+                let opened = state & ((1 << n) - 1);
+                let positions: Vec<usize> = (0..actors).map(|actor| (state >> n) / n.pow(actor as u32) % n).collect();
+                let mut choices = vec![(0, opened, 0)];
+                for (actor, &position) in positions.iter().enumerate() {
+                    let place = n.pow(actor as u32);
+                    let mut next_choices = vec!();
+                    for &(pressure, opened, moved) in choices.iter() {
+                        if scan[position].0 > 0 && opened & 1 << position == 0 {
+                            next_choices.push((pressure + (time_remaining - 1) * scan[position].0, opened | 1 << position, moved + place * position));
+                        }
+                        for &neighbour in scan[position].1.iter() {
+                            next_choices.push((pressure, opened, moved + place * neighbour));
+                        }
+                    }
+                    choices = next_choices;
+                }
+                *result = choices.into_iter()
+                    .map(|(pressure, opened, moved)| pressure + best[moved << n | opened])
+                    .max()
+                    .unwrap();
+            }
+            best = next;
+        }
+        best[0]
+    }
+
+    fn scan() -> impl Strategy<Value = Scan> {
+        (2usize..=NAMES.len())
+            .prop_flat_map(|n| (
+                prop::collection::vec(prop_oneof![Just(0), 1..25], n - 1),
+                // A random tree to keep every valve reachable, plus a few shortcuts
+                (1..n).map(|i| 0..i).collect::<Vec<_>>(),
+                prop::collection::vec((0..n, 0..n), 0..n),
+            ))
+            .prop_map(|(flow_rates, parents, shortcuts)| {
+                let mut scan: Scan = Some(0).into_iter().chain(flow_rates).map(|f| (f, vec!())).collect();
+                let edges = parents.into_iter().enumerate().map(|(i, p)| (i + 1, p)).chain(shortcuts);
+                for (a, b) in edges {
+                    if a != b && !scan[a].1.contains(&b) {
+                        scan[a].1.push(b);
+                        scan[b].1.push(a);
+                    }
+                }
+                scan
+            })
+    }
+
+    fn solve(scan: &Scan, agents: usize, minutes: i32) -> i32 {
+        let valves = Valves::new(scan.iter().enumerate().map(|(i, (flow_rate, tunnels))| {
+            (NAMES[i], *flow_rate, tunnels.iter().map(|&t| NAMES[t]).collect())
+        }));
+        let plan = plan(&valves, agents, minutes);
+        assert_eq!(replay(&valves, &plan, minutes), plan.pressure);
+        plan.pressure
+    }
+
+    // Follows every schedule to check it can be done in time, returning the pressure it releases
+    fn replay(valves: &Valves, plan: &Plan, minutes: i32) -> i32 {
+        let mut opened = FxHashSet::default();
+        let mut pressure = 0;
+        for schedule in plan.schedules.iter() {
+            let (mut position, mut minute) = (valves.start, 0);
+            for opening in schedule {
+                assert!(opened.insert(opening.valve), "{} is opened twice", valves.names[opening.valve]);
+                minute += valves.distances[position][opening.valve] + 1;
+                assert_eq!(minute, opening.minute);
+                assert!(minute < minutes);
+                pressure += (minutes - minute) * valves.flow_rates[opening.valve];
+                position = opening.valve;
+            }
+        }
+        pressure
+    }
+
+    #[test]
+    fn sample_plans() {
+        let valves = Valves::new(parse_input(include_str!("../inputs/day16_input_test.txt")).unwrap().into_iter());
+        let distance = |from, to| {
+            let index = |name| valves.names.iter().position(|&n| n == name).unwrap();
+            valves.distances[index(from)][index(to)]
         };
+        assert_eq!(distance("AA", "JJ"), 2);
+        assert_eq!(distance("HH", "AA"), 5);
+        assert_eq!(distance("CC", "CC"), 0);
 
-        let m = max(time_delta_with_flipping, time_delta_without_flipping);
-        memo.insert(memo_key, m);
-        m
+        let p1 = part_one(&valves);
+        assert_eq!(p1.pressure, 1651);
+        assert_eq!(
+            render_plan(&valves, &p1),
+            "  You: DD at minute 2, BB at minute 5, JJ at minute 9, HH at minute 17, EE at minute 21, CC at minute 24",
+        );
+        let p2 = part_two(&valves, 2, 26);
+        assert_eq!(p2.pressure, 1707);
+        assert_eq!(replay(&valves, &p2, 26), 1707);
+        // Three agents have time to open everything, with some to spare
+        let p3 = part_two(&valves, 3, 26);
+        assert_eq!(replay(&valves, &p3, 26), p3.pressure);
+        assert_eq!(p3.schedules.iter().map(Vec::len).sum::<usize>(), 6);
     }
 
-    helper(starting_time, START_VALVE, valves, &relevant_valves, starting_state, &mut memo)
+    proptest! {
+        #[test]
+        fn searches_match_walking(scan in scan()) {
+            prop_assert_eq!(solve(&scan, 1, 30), most_pressure(&scan, 30, 1));
+            prop_assert_eq!(solve(&scan, 2, 26), most_pressure(&scan, 26, 2));
+        }
+    }
+
+    proptest! {
+        // Walking gets slow with three actors
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn three_agents_match_walking(scan in scan()) {
+            prop_assert_eq!(solve(&scan, 3, 12), most_pressure(&scan, 12, 3));
+        }
+    }
 }
@@ -1,311 +1,648 @@
+use std::env;
 use std::fs;
 use std::fmt;
+use std::io::{self, Write};
 use std::time::Instant;
 
-use rustc_hash::{FxHashSet, FxHashMap};
-use ringbuffer::{ConstGenericRingBuffer, RingBufferExt, RingBufferWrite, RingBuffer};
+use rustc_hash::FxHashMap;
 
 
 const FILE_PATH: &str = "inputs/day17_input.txt";
-const CHAMBER_HEIGHT: usize = 64;
 const P1_DROP_COUNT: u64 = 2022;
 const P2_DROP_COUNT: u64 = 1_000_000_000_000;
+const DEFAULT_WIDTH: u32 = 7;
+// Rows are u64s and the cells past the right wall count as rock, so one bit has to stay spare
+const MAX_WIDTH: u32 = u64::BITS - 1;
+// Rocks start two cells in from the left wall
+const SPAWN_COLUMN: u32 = 2;
+const SPAWN_GAP: isize = 3;
+// How many rows from the top down are compared when looking for a cycle. The window grows to take
+// in every row a rock looks at, and a chamber needing more than the most never gets extrapolated
+const MIN_WINDOW_DEPTH: usize = 64;
+const MAX_WINDOW_DEPTH: usize = 8192;
+// How many rocks to drop looking for a cycle before giving up on one
+const MAX_CYCLE_SEARCH: usize = 2_000_000;
+// Stands in for the rows below the floor, every cell set so it can't match a row of rock
+const FLOOR: u64 = u64::MAX;
+// Any odd number will do, as no power of one wraps around to zero
+const HASH_BASE: u64 = 0x9E37_79B9_7F4A_7C15;
+// How much of the chamber step mode draws below the falling rock
+const FRAME_ROWS: usize = 30;
+const DEFAULT_PIECES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+// A rock as rows from the bottom up, with bit x set for the cells in column x from the left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Piece {
+    rows: Vec<u64>,
+    width: u32,
+}
+
+impl Piece {
+    fn from_diagram(diagram: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = diagram.lines().collect();
+        if let Some(ch) = diagram.chars().find(|&ch| !matches!(ch, '#' | '.' | '\n')) {
+            return Err(format!("Unknown cell {ch:?} in piece\n{diagram}"));
+        }
+        if lines.iter().any(|line| !line.contains('#')) {
+            return Err(format!("Every row of a piece needs a rock in it\n{diagram}"));
+        }
+        // Leading empty columns don't change how a piece falls
+        let left = lines.iter().map(|line| line.find('#').unwrap()).min().unwrap_or(0);
+        let width = lines.iter().map(|line| line.rfind('#').unwrap() + 1 - left).max().unwrap_or(0);
+        if width > MAX_WIDTH as usize {
+            return Err(format!("A piece can be at most {MAX_WIDTH} cells wide\n{diagram}"));
+        }
+        let rows = lines.iter().rev()
+            .map(|line| line.bytes().enumerate()
+                .filter(|&(_, cell)| cell == b'#')
+                .fold(0, |row, (x, _)| row | 1 << (x - left)))
+            .collect();
+        Ok(Piece { rows, width: width as u32 })
+    }
+}
 
-#[derive(Debug)]
-enum Piece {
-    Dash,
-    Plus,
-    L,
-    I,
-    Square
+/// Reads the pieces from diagrams like the puzzle's, `#` for rock and `.` for air, with a blank
+/// line between pieces. They fall in the order they are given.
+pub(crate) fn parse_pieces(in_str: &str) -> Result<Vec<Piece>, String> {
+    let pieces = in_str.trim().split("\n\n").map(|diagram| Piece::from_diagram(diagram.trim())).collect::<Result<Vec<_>, _>>()?;
+    if pieces.is_empty() || pieces.iter().any(|piece| piece.rows.is_empty()) {
+        return Err("There are no pieces".to_string());
+    }
+    Ok(pieces)
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Jet {
+pub(crate) enum Jet {
     Left,
     Right,
 }
 
 impl Jet {
-    fn from_char(ch: char) -> Self {
+    fn from_char(ch: char) -> Result<Self, String> {
         match ch {
-            '<' => Jet::Left,
-            '>' => Jet::Right,
-            _ => unreachable!(),
+            '<' => Ok(Jet::Left),
+            '>' => Ok(Jet::Right),
+            _ => Err(format!("Unknown jet {ch:?}")),
         }
     }
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Jet>, String> {
+    let jets = in_str.trim().chars().map(Jet::from_char).collect::<Result<Vec<_>, _>>()?;
+    if jets.is_empty() {
+        return Err("There are no jets".to_string());
+    }
+    Ok(jets)
+}
+
 struct Chamber {
-    rocks: ConstGenericRingBuffer<u8, CHAMBER_HEIGHT>,
-    top: i32,
+    width: u32,
+    // Rows from the floor up, bit x set for rock in column x from the left
+    rocks: Vec<u64>,
 }
 
 impl Chamber {
-    fn new() -> Self {
-        Self { rocks: ConstGenericRingBuffer::new(), top: 0 }
+    fn new(width: u32) -> Self {
+        Self { width, rocks: vec!() }
+    }
+
+    fn top(&self) -> i32 {
+        self.rocks.len() as i32
     }
 
-    fn insert(&mut self, bytes: PieceCoords, offset: isize) {
-        let mut i: i32 = 3;
-        let mut comparison_line = offset;
-        while i >= 0 && comparison_line < 0 {
-            self.rocks[comparison_line] |= bytes[i as usize];
-            i -= 1;
-            comparison_line += 1;
+    // Whether a piece with its bottom row at `y` overlaps the floor or any rock
+    fn fits(&self, rows: &[u64], y: isize) -> bool {
+        y >= 0 && rows.iter().enumerate().all(|(i, row)| {
+            self.rocks.get(y as usize + i).is_none_or(|rock| rock & row == 0)
+        })
+    }
+
+    // A row by its height, the floor being everything below row 0
+    fn row(&self, line: isize) -> u64 {
+        if line < 0 {
+            FLOOR
+        } else {
+            self.rocks.get(line as usize).copied().unwrap_or(0)
         }
+    }
 
-        while i >= 0 {
-            if bytes[i as usize] != 0 {
-                self.top += 1;
-                self.rocks.push(bytes[i as usize]);
-            } else {
-                break;
+    // `depth` rows from just under `top` down
+    fn window(&self, top: i32, depth: usize) -> Vec<u64> {
+        (1..=depth as isize).map(|i| self.row(top as isize - i)).collect()
+    }
+
+    fn insert(&mut self, rows: &[u64], y: isize) {
+        for (i, row) in rows.iter().enumerate() {
+            let line = y as usize + i;
+            if line >= self.rocks.len() {
+                self.rocks.resize(line + 1, 0);
             }
-            i -= 1;
+            self.rocks[line] |= row;
+        }
+    }
+
+    // Draws the chamber the way the puzzle does, from the top of the falling rock or the tower
+    // down. Only the top `max_rows` rows are drawn, the floor along with them if it's in reach
+    fn render(&self, falling: Option<(&[u64], isize)>, max_rows: usize) -> String {
+        let falling_top = falling.map_or(0, |(rows, y)| y as usize + rows.len());
+        let top = falling_top.max(self.rocks.len());
+        let bottom = top.saturating_sub(max_rows);
+        let mut lines = vec!();
+        for line in (bottom..top).rev() {
+            let rock = self.rocks.get(line).copied().unwrap_or(0);
+            let piece = falling
+                .and_then(|(rows, y)| line.checked_sub(y as usize).and_then(|i| rows.get(i)))
+                .copied()
+                .unwrap_or(0);
+            let cells: String = (0..self.width)
+                .map(|x| if piece & 1 << x != 0 { '@' } else if rock & 1 << x != 0 { '#' } else { '.' })
+                .collect();
+            lines.push(format!("|{cells}|"));
+        }
+        if bottom == 0 {
+            lines.push(format!("+{}+", "-".repeat(self.width as usize)));
         }
+        lines.join("\n")
     }
 }
 
 impl fmt::Display for Chamber {
     // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for y in 1..=std::cmp::min(self.rocks.len(), 10) {
-            writeln!(f, "{:08b}", self.rocks.get(y as isize * -1).unwrap())?;
-        }
-        Ok(())
+        writeln!(f, "{}", self.render(None, 10))
     }
 }
 
 #[derive(Default, Hash, Eq, PartialEq, Debug)]
 struct Point(u32, u32);
 
-type PieceCoords = [u8; 4];
-
-impl Piece {
-    fn get_bytes(&self) -> PieceCoords {
-        use Piece::*;
-        match self {
-            // ###
-            Dash => [
-                0b_0000000_0,
-                0b_0000000_0,
-                0b_0000000_0,
-                0b_0011110_0,
-            ],
-
-            //  # 
-            // ###
-            //  #
-            Plus => [
-                0b_0000000_0,
-                0b_0001000_0,
-                0b_0011100_0,
-                0b_0001000_0,
-            ],
-
-            //   #
-            //   #
-            // ###
-            L => [
-                0b_0000000_0,
-                0b_0000100_0,
-                0b_0000100_0,
-                0b_0011100_0,
-            ],
-
-            // #
-            // #
-            // #
-            // #
-            I => [
-                0b_0010000_0,
-                0b_0010000_0,
-                0b_0010000_0,
-                0b_0010000_0,
-            ],
-
-            // ##
-            // ##
-            Square => [
-                0b_0000000_0,
-                0b_0000000_0,
-                0b_0011000_0,
-                0b_0011000_0,
-            ],
-        }
-    }
-}
-
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day17");
-    let jets = contents.trim().chars().map(Jet::from_char).enumerate().cycle();
-    let (p1, p2) = part_one(jets);
+    let jets = parse_input(&contents).expect("Could not parse input for day17");
+    let args: Vec<String> = env::args().collect();
+    let flag_value = |flag: &str| args.iter().position(|arg| arg == flag).map(|i| {
+        args.get(i + 1).unwrap_or_else(|| panic!("{flag} needs a value"))
+    });
+    // --pieces <file> swaps in other rocks, --width <n> another chamber
+    let pieces = match flag_value("--pieces") {
+        Some(path) => fs::read_to_string(path).expect("Could not read the pieces"),
+        None => DEFAULT_PIECES.to_string(),
+    };
+    let pieces = parse_pieces(&pieces).expect("Could not parse the pieces");
+    let width = flag_value("--width").map_or(DEFAULT_WIDTH, |w| w.parse().expect("--width must be a number"));
+    check_width(&pieces, width).expect("--width doesn't fit the pieces");
+    let (p1, p2) = part_one(jets.iter().copied().enumerate().cycle(), &pieces, width, P1_DROP_COUNT, P2_DROP_COUNT);
     println!("Elapsed: {:?}", start.elapsed());
+    // --steps <n> draws every push and drop of the first n rocks
+    if let Some(steps) = flag_value("--steps") {
+        let steps = steps.parse().expect("--steps must be a number");
+        show_steps(&jets, &pieces, width, steps, &mut io::stdout()).expect("Could not draw the steps");
+    }
     println!("D17P1: {p1:?}");
-    println!("D17P1: {p2:?}");
+    println!("D17P1: {}", p2.map_or("no cycle within reach".to_string(), |p2| p2.to_string()));
 }
 
-fn check_push(bytes: PieceCoords, jet: Jet, chamber: &Chamber, offset: isize) -> bool {
-    let mut out: PieceCoords = [0; 4];
-    match jet {
-        Jet::Left => {
-            for (i, &byte) in bytes.iter().rev().enumerate() {
-                if byte & 0b10000000 != 0{
-                    return false;
-                }
-                out[i] = byte << 1;
-            }
-        },
-        Jet::Right => {
-            for (i, &byte) in bytes.iter().rev().enumerate() {
-                if byte & 0b00000010 != 0 {
-                    return false;
-                }
-                out[i] = byte >> 1;
-            }
-        },
+fn check_width(pieces: &[Piece], width: u32) -> Result<(), String> {
+    if width > MAX_WIDTH {
+        return Err(format!("The chamber can be at most {MAX_WIDTH} columns wide"));
     }
+    match pieces.iter().position(|piece| SPAWN_COLUMN + piece.width > width) {
+        Some(i) => Err(format!("Piece {i} doesn't fit in a chamber {width} columns wide")),
+        None => Ok(()),
+    }
+}
 
-    if offset < 0 {
-        let mut comparison_line = offset; 
-        let mut i = 0;
-        while i < 4 && comparison_line < 0 {
-            if let Some(comp) = chamber.rocks.get(comparison_line) {
-                if out[i as usize] & comp != 0 {
-                    return false
-                }
+// The piece pushed one column over, unless a wall or rock is in the way
+fn check_push(rows: &[u64], jet: Jet, chamber: &Chamber, y: isize) -> Option<Vec<u64>> {
+    let pushed: Vec<u64> = match jet {
+        Jet::Left if rows.iter().all(|row| row & 1 == 0) => rows.iter().map(|row| row >> 1).collect(),
+        Jet::Right if rows.iter().all(|row| row & 1 << (chamber.width - 1) == 0) => rows.iter().map(|row| row << 1).collect(),
+        _ => return None,
+    };
+    chamber.fits(&pushed, y).then_some(pushed)
+}
+
+fn check_drop(rows: &[u64], chamber: &Chamber, y: isize) -> bool {
+    chamber.fits(rows, y - 1)
+}
+
+// Drops rocks one at a time, drawing a frame for the new rock and after every push and drop like the
+// puzzle's walkthrough. It moves rocks with the same checks the solver uses
+fn show_steps(jets: &[Jet], pieces: &[Piece], width: u32, drop_count: usize, out: &mut impl Write) -> io::Result<()> {
+    let mut chamber = Chamber::new(width);
+    let mut jets = jets.iter().cycle();
+    for (i, piece) in pieces.iter().cycle().take(drop_count).enumerate() {
+        let mut rows: Vec<u64> = piece.rows.iter().map(|row| row << SPAWN_COLUMN).collect();
+        let mut y = chamber.top() as isize + SPAWN_GAP;
+        let mut frame = |chamber: &Chamber, title: &str, rows: &[u64], y: isize| {
+            writeln!(out, "{title}:\n{}\n", chamber.render(Some((rows, y)), FRAME_ROWS + rows.len()))
+        };
+        frame(&chamber, if i == 0 { "The first rock begins falling" } else { "A new rock begins falling" }, &rows, y)?;
+        loop {
+            let &jet = jets.next().unwrap();
+            let direction = match jet { Jet::Left => "left", Jet::Right => "right" };
+            match check_push(&rows, jet, &chamber, y) {
+                Some(pushed) => {
+                    rows = pushed;
+                    frame(&chamber, &format!("Jet of gas pushes rock {direction}"), &rows, y)?;
+                },
+                None => frame(&chamber, &format!("Jet of gas pushes rock {direction}, but nothing happens"), &rows, y)?,
+            }
+            if check_drop(&rows, &chamber, y) {
+                y -= 1;
+                frame(&chamber, "Rock falls 1 unit", &rows, y)?;
             } else {
-                break
+                chamber.insert(&rows, y);
+                writeln!(out, "Rock falls 1 unit, causing it to come to rest:\n{}\n", chamber.render(None, FRAME_ROWS + rows.len()))?;
+                break;
             }
-            i += 1;
-            comparison_line += 1;
         }
     }
+    Ok(())
+}
 
-    true
+// A hash of the top `depth` rows of the chamber, kept up to date as rocks land so that looking for
+// a repeat costs the same however deep the window goes
+struct Window {
+    depth: usize,
+    hash: u64,
+    // HASH_BASE to the power of each depth below the top
+    powers: Vec<u64>,
 }
 
-fn check_drop(bytes: PieceCoords, chamber: &Chamber, offset: isize) -> bool {
-    // We are still hovering above the last roof
-    if offset > 0 {
-        return true
+impl Window {
+    fn new(chamber: &Chamber, depth: usize) -> Self {
+        let mut window = Window { depth, hash: 0, powers: vec![1] };
+        for (i, row) in chamber.window(chamber.top(), depth).into_iter().enumerate() {
+            window.hash = window.hash.wrapping_add(row.wrapping_mul(window.power(i)));
+        }
+        window
     }
 
-    let mut i: i32 = 3;
-    let mut comparison_line = (offset - 1) as isize;
+    fn power(&mut self, i: usize) -> u64 {
+        while self.powers.len() <= i {
+            let next = self.powers[self.powers.len() - 1].wrapping_mul(HASH_BASE);
+            self.powers.push(next);
+        }
+        self.powers[i]
+    }
 
-    while i >= 0 && comparison_line < 0 {
-        if let Some(comp) = chamber.rocks.get(comparison_line) {
-            if bytes[i as usize] & comp != 0 {
-                return false
+    // Lands a rock, then accounts for the cells it filled in and every row that moved further
+    // down, came into the window or dropped out of it
+    fn insert(&mut self, chamber: &mut Chamber, rows: &[u64], y: isize) {
+        let old_top = chamber.top() as isize;
+        for (i, &row) in rows.iter().enumerate() {
+            let below_top = old_top - 1 - (y + i as isize);
+            // Rocks never overlap, so filling cells in just adds their bits
+            if (0..self.depth as isize).contains(&below_top) {
+                let power = self.power(below_top as usize);
+                self.hash = self.hash.wrapping_add(row.wrapping_mul(power));
             }
-        } else {
-            return false
         }
-        i -= 1;
-        comparison_line += 1;
+        chamber.insert(rows, y);
+        let top = chamber.top() as isize;
+        if top == old_top {
+            return;
+        }
+        let power = self.power((top - old_top) as usize);
+        self.hash = self.hash.wrapping_mul(power);
+        for line in old_top..top {
+            let power = self.power((top - 1 - line) as usize);
+            self.hash = self.hash.wrapping_add(chamber.row(line).wrapping_mul(power));
+        }
+        for line in old_top - self.depth as isize..top - self.depth as isize {
+            let power = self.power((top - 1 - line) as usize);
+            self.hash = self.hash.wrapping_sub(chamber.row(line).wrapping_mul(power));
+        }
     }
+}
 
-    // We have not pushed something this far into the buffer
-    true
+// A rock that came to rest: which piece it was, how many columns it ended up pushed right, the row
+// its bottom came to rest on, and how tall the tower was before it
+struct Landed {
+    piece: usize,
+    column: u32,
+    y: isize,
+    top: i32,
 }
 
-fn get_chamber_arr(chamber: &Chamber) -> [u8; CHAMBER_HEIGHT] {
-    let mut out = [0; CHAMBER_HEIGHT];
-    for i in 1..=CHAMBER_HEIGHT {
-        out[i - 1] = *chamber.rocks.get(-1 * i as isize).unwrap();
+// The window as it was before `landed[0]` came to rest, found by taking every rock since back out
+fn window_before(chamber: &Chamber, pieces: &[Piece], landed: &[Landed], depth: usize) -> Vec<u64> {
+    let top = landed[0].top;
+    let mut window = chamber.window(top, depth);
+    for rock in landed {
+        for (i, row) in pieces[rock.piece].rows.iter().enumerate() {
+            let below_top = top as isize - 1 - (rock.y + i as isize);
+            if (0..depth as isize).contains(&below_top) {
+                window[below_top as usize] &= !(row << rock.column);
+            }
+        }
     }
-
-    out
+    window
 }
 
-fn part_one(mut jets: impl Iterator<Item = (usize, Jet)>) -> (i32, u64) {
-    let mut dropped_count = 0;
-    let mut chamber = Chamber::new();
-    let mut cache:FxHashSet<(usize, usize, [u8; CHAMBER_HEIGHT])> = FxHashSet::default();
-    let mut pieces = [
-        Piece::Dash,
-        Piece::Plus, 
-        Piece::L, 
-        Piece::I, 
-        Piece::Square
-    ].iter().enumerate().cycle();
-
-    let mut offset_height = 0;
-    let mut p1 = 0;
-    let mut p2: u64 = 0;
-    let (mut cycle_index, mut cycle_length) = (0, 0);
-    let mut cycle_i = 0;
-    let mut partial_cycle_heights = FxHashMap::default();
-
-    'outer: loop {
-        let (piece_index, piece) = pieces.next().unwrap();
-        let mut new_piece = true;
-
-        let (mut piece_bytes, mut offset) = (piece.get_bytes(), 3);
+// Once the piece, the jet and the top rows of the chamber repeat, and no rock in between looked
+// further down than those rows, every rock from then on lands the same as one a cycle earlier.
+// Wide chambers can keep pits deeper than any window, with floors that don't settle down for
+// millions of rocks. There's no telling what happens next in those, so only the drops that can be
+// played out get a height
+fn part_one(jets: impl Iterator<Item = (usize, Jet)>, pieces: &[Piece], width: u32, p1_drop_count: u64, p2_drop_count: u64) -> (i32, Option<u64>) {
+    let mut jets = jets.peekable();
+    let mut piece_order = pieces.iter().enumerate().cycle();
+    let mut chamber = Chamber::new(width);
+    let mut window = Window::new(&chamber, MIN_WINDOW_DEPTH);
+    // Where in `landed` each piece, jet and window was seen
+    let mut seen: FxHashMap<(usize, usize, u64), usize> = FxHashMap::default();
+    // Every rock since the search for a cycle last started over, the first of them rock number
+    // `first_landed`
+    let mut landed: Vec<Landed> = vec!();
+    let mut first_landed = 0;
+    let mut searching = true;
+    let (mut p1, mut p2) = (None, None);
+
+    for dropped_count in 0.. {
+        if dropped_count == p1_drop_count {
+            p1 = Some(chamber.top());
+        }
+        if dropped_count == p2_drop_count {
+            p2 = Some(chamber.top() as u64);
+        }
+        match (p1, p2) {
+            (Some(p1), Some(p2)) => return (p1, Some(p2)),
+            (Some(p1), None) if !searching => return (p1, None),
+            _ => (),
+        }
 
-        loop {
-            let (jet_index, jet) = jets.next().unwrap();
-            if new_piece && dropped_count != 0 {
-                let chamber_arr = get_chamber_arr(&chamber);
-                if cache.contains(&(piece_index, jet_index, chamber_arr)) {
-                    if cycle_index == 0 {
-                        // How many pieces are dropped before the start of the first cycle
-                        cycle_index = dropped_count;
-                        offset_height = chamber.top;
-                        cache.clear();
-                        cache.insert((piece_index, jet_index, chamber_arr));
-                    } else {
-                        if cycle_length == 0 {
-                            cycle_length = dropped_count - cycle_index;
-                            let cycle_height = chamber.top - offset_height;
-                            let cycle_gap = P2_DROP_COUNT - cycle_index as u64;
-                            let full_cycles = cycle_gap / cycle_length;
-                            let partial_cycle = cycle_gap % cycle_length;
-
-                            p2 += offset_height as u64;
-                            p2 += cycle_height as u64 * full_cycles;
-                            p2 += partial_cycle_heights.get(&(partial_cycle)).unwrap();
-
-                            if p1 != 0 { break 'outer };
-                        }
-                    }
-                } else {
-                    cache.insert((piece_index, jet_index, chamber_arr));
+        let (piece_index, piece) = piece_order.next().unwrap();
+        let &(jet_index, _) = jets.peek().unwrap();
+        let mut key = (piece_index, jet_index, window.hash);
+        match seen.get(&key) {
+            // The hashes could match by chance, the rows themselves can't
+            Some(&start) if window_before(&chamber, pieces, &landed[start..], window.depth) == chamber.window(chamber.top(), window.depth) => {
+                let start_top = landed[start].top;
+                let deepest_read = landed[start..].iter().map(|rock| rock.y - 1).min().unwrap();
+                let needed_depth = (start_top as isize - deepest_read) as usize;
+                if needed_depth <= window.depth {
+                    let cycle_start = first_landed + start as u64;
+                    let cycle_length = (landed.len() - start) as u64;
+                    let cycle_height = (chamber.top() - start_top) as u64;
+                    let height_after = |drop_count: u64| {
+                        let (full_cycles, partial_cycle) = ((drop_count - cycle_start) / cycle_length, (drop_count - cycle_start) % cycle_length);
+                        landed[start + partial_cycle as usize].top as u64 + full_cycles * cycle_height
+                    };
+                    return (p1.unwrap_or_else(|| height_after(p1_drop_count) as i32), Some(p2.unwrap_or_else(|| height_after(p2_drop_count))));
                 }
-            }
-            new_piece = false;
-
-            if check_push(piece_bytes, jet, &chamber, offset) {
-                for byte in piece_bytes.iter_mut() {
-                    match jet {
-                        Jet::Right => *byte >>= 1,
-                        Jet::Left => *byte <<= 1,
-                    }
+                // A rock looked below the window, so what it found there might not be the same
+                // next time round. Start over comparing enough rows to take that in
+                if needed_depth <= MAX_WINDOW_DEPTH {
+                    window = Window::new(&chamber, (needed_depth * 2).min(MAX_WINDOW_DEPTH));
+                    key.2 = window.hash;
+                    seen.clear();
+                    landed.clear();
+                    first_landed = dropped_count;
+                } else {
+                    searching = false;
                 }
-            }
+            },
+            _ => (),
+        }
+        if landed.len() == MAX_CYCLE_SEARCH {
+            searching = false;
+        }
+        if searching {
+            seen.insert(key, landed.len());
+        }
 
-            if check_drop(piece_bytes, &chamber, offset) {
-                offset -= 1;
+        let mut rows: Vec<u64> = piece.rows.iter().map(|row| row << SPAWN_COLUMN).collect();
+        let mut y = chamber.top() as isize + SPAWN_GAP;
+        loop {
+            let (_, jet) = jets.next().unwrap();
+            if let Some(pushed) = check_push(&rows, jet, &chamber, y) {
+                rows = pushed;
+            }
+            if check_drop(&rows, &chamber, y) {
+                y -= 1;
             } else {
-                chamber.insert(piece_bytes, offset);
-                if offset_height != 0 {
-                    cycle_i += 1;
-                    partial_cycle_heights.insert(cycle_i, (chamber.top - offset_height) as u64);
+                let top = chamber.top();
+                window.insert(&mut chamber, &rows, y);
+                if searching {
+                    let column = rows[0].trailing_zeros() - piece.rows[0].trailing_zeros();
+                    landed.push(Landed { piece: piece_index, column, y, top });
                 }
                 break;
             }
         }
-        dropped_count += 1;
-        if dropped_count == P1_DROP_COUNT { 
-            p1 = chamber.top;
-            if p2 != 0 {
-                break 'outer;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rustc_hash::FxHashSet;
+
+    // Drops every rock one cell at a time into an unbounded set of points
+    fn tower_height(jets: &[Jet], diagrams: &[String], width: i64, drop_count: u64) -> i64 {
+        // Cells from the bottom left of each diagram
+        let shapes: Vec<Vec<(i64, i64)>> = diagrams.iter()
+            .map(|diagram| {
+                let lines: Vec<&str> = diagram.lines().collect();
+                let left = lines.iter().filter_map(|line| line.find('#')).min().unwrap() as i64;
+                lines.iter().rev().enumerate()
+                    .flat_map(|(y, line)| line.char_indices().filter(|&(_, c)| c == '#').map(move |(x, _)| (x as i64 - left, y as i64)))
+                    .collect()
+            })
+            .collect();
+        let mut rocks = FxHashSet::default();
+        let mut height = 0;
+        let mut jets = jets.iter().cycle();
+        for shape in shapes.iter().cycle().take(drop_count as usize) {
+            let (mut x, mut y) = (2, height + 3);
+            let fits = |x: i64, y: i64, rocks: &FxHashSet<(i64, i64)>| shape.iter()
+                .all(|&(dx, dy)| (0..width).contains(&(x + dx)) && y + dy >= 0 && !rocks.contains(&(x + dx, y + dy)));
+            loop {
+                let dx = match jets.next().unwrap() { Jet::Left => -1, Jet::Right => 1 };
+                if fits(x + dx, y, &rocks) {
+                    x += dx;
+                }
+                if !fits(x, y - 1, &rocks) {
+                    break;
+                }
+                y -= 1;
+            }
+            for &(dx, dy) in shape.iter() {
+                rocks.insert((x + dx, y + dy));
+                height = height.max(y + dy + 1);
             }
         }
+        height
     }
 
-    (p1, p2)
+    fn jets() -> impl Strategy<Value = Vec<Jet>> {
+        prop::collection::vec(prop_oneof![Just(Jet::Left), Just(Jet::Right)], 1..40)
+    }
+
+    // Up to four pieces of up to 4x4 cells, each row with at least one rock
+    fn diagrams() -> impl Strategy<Value = Vec<String>> {
+        let diagram = prop::collection::vec(1u8..16, 1..=4).prop_map(|rows| {
+            rows.iter()
+                .map(|row| (0..4).map(|x| if row & 1 << x != 0 { '#' } else { '.' }).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        prop::collection::vec(diagram, 1..=4)
+    }
+
+    #[test]
+    fn parses_pieces() {
+        let pieces = parse_pieces(DEFAULT_PIECES).unwrap();
+        assert_eq!(pieces.len(), 5);
+        assert_eq!(pieces[1], Piece { rows: vec![0b010, 0b111, 0b010], width: 3 });
+        // The L's corner is on the right, and rows go from the bottom up
+        assert_eq!(pieces[2], Piece { rows: vec![0b111, 0b100, 0b100], width: 3 });
+        assert_eq!(parse_pieces("..#\n.##").unwrap()[0], Piece { rows: vec![0b11, 0b10], width: 2 });
+        assert!(parse_pieces("#.\n..").is_err());
+        assert!(parse_pieces("#o").is_err());
+        assert!(parse_pieces(&"#".repeat(64)).is_err());
+        assert!(check_width(&pieces, 6).is_ok());
+        assert!(check_width(&pieces, 5).is_err());
+        assert!(check_width(&pieces, 64).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn cycle_extrapolation_matches_simulation(jets in jets(), p1_drop_count in 1u64..300, p2_drop_count in 1u64..3000) {
+            let pieces = parse_pieces(DEFAULT_PIECES).unwrap();
+            let diagrams: Vec<String> = DEFAULT_PIECES.split("\n\n").map(str::to_string).collect();
+            let (p1, p2) = part_one(jets.iter().copied().enumerate().cycle(), &pieces, DEFAULT_WIDTH, p1_drop_count, p2_drop_count);
+            prop_assert_eq!(p1 as i64, tower_height(&jets, &diagrams, DEFAULT_WIDTH as i64, p1_drop_count));
+            prop_assert_eq!(p2.unwrap() as i64, tower_height(&jets, &diagrams, DEFAULT_WIDTH as i64, p2_drop_count));
+        }
+
+        #[test]
+        fn other_pieces_and_widths(jets in jets(), diagrams in diagrams(), extra_width in 0u32..8, p2_drop_count in 1u64..1000) {
+            let pieces = parse_pieces(&diagrams.join("\n\n")).unwrap();
+            let width = pieces.iter().map(|piece| piece.width).max().unwrap() + SPAWN_COLUMN + extra_width;
+            let (_, p2) = part_one(jets.iter().copied().enumerate().cycle(), &pieces, width, 1, p2_drop_count);
+            prop_assert_eq!(p2.unwrap() as i64, tower_height(&jets, &diagrams, width as i64, p2_drop_count));
+        }
+    }
+
+    #[test]
+    fn widest_chamber() {
+        // Jets from a xorshift, which this wide a chamber takes tens of thousands of rocks and a
+        // couple of deeper windows to find the cycle in
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let jets: Vec<Jet> = (0..1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if state >> 40 & 1 == 0 { Jet::Left } else { Jet::Right }
+            })
+            .collect();
+        let pieces = parse_pieces(DEFAULT_PIECES).unwrap();
+        let diagrams: Vec<String> = DEFAULT_PIECES.split("\n\n").map(str::to_string).collect();
+        let (p1, p2) = part_one(jets.iter().copied().enumerate().cycle(), &pieces, MAX_WIDTH, P1_DROP_COUNT, 100_000);
+        assert_eq!(p1 as i64, tower_height(&jets, &diagrams, MAX_WIDTH as i64, P1_DROP_COUNT));
+        assert_eq!(p2.map(|p2| p2 as i64), Some(tower_height(&jets, &diagrams, MAX_WIDTH as i64, 100_000)));
+        assert!(part_one(jets.iter().copied().enumerate().cycle(), &pieces, MAX_WIDTH, P1_DROP_COUNT, P2_DROP_COUNT).1.is_some());
+    }
+
+    #[test]
+    fn steps_through_the_example() {
+        let jets = parse_input(include_str!("../inputs/day17_input_test.txt")).unwrap();
+        let pieces = parse_pieces(DEFAULT_PIECES).unwrap();
+        let mut out = vec!();
+        show_steps(&jets, &pieces, DEFAULT_WIDTH, 2, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let expected = "\
+The first rock begins falling:
+|..@@@@.|
+|.......|
+|.......|
+|.......|
++-------+
+
+Jet of gas pushes rock right:
+|...@@@@|
+|.......|
+|.......|
+|.......|
++-------+
+
+Rock falls 1 unit:
+|...@@@@|
+|.......|
+|.......|
++-------+
+
+Jet of gas pushes rock right, but nothing happens:
+|...@@@@|
+|.......|
+|.......|
++-------+
+
+Rock falls 1 unit:
+|...@@@@|
+|.......|
++-------+
+
+Jet of gas pushes rock right, but nothing happens:
+|...@@@@|
+|.......|
++-------+
+
+Rock falls 1 unit:
+|...@@@@|
++-------+
+
+Jet of gas pushes rock left:
+|..@@@@.|
++-------+
+
+Rock falls 1 unit, causing it to come to rest:
+|..####.|
++-------+
+
+A new rock begins falling:
+|...@...|
+|..@@@..|
+|...@...|
+|.......|
+|.......|
+|.......|
+|..####.|
++-------+
+";
+        assert!(out.starts_with(expected), "{out}");
+        assert!(out.ends_with("\
+Rock falls 1 unit, causing it to come to rest:
+|...#...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+
+"), "{out}");
+    }
 }
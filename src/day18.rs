@@ -1,3 +1,6 @@
+mod mesh;
+
+use std::env;
 use std::fs;
 use std::cmp::{min, max};
 use std::collections::VecDeque;
@@ -19,16 +22,18 @@ const OFFSETS: [(i32, i32, i32); 6] = [
 ];
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
-struct Cube(i32, i32, i32);
+pub(crate) struct Cube(i32, i32, i32);
 
 impl Cube {
-    fn from_str(input: &str) -> Self {
-        let mut split = input.split(',');
-        Cube (
-            split.next().unwrap().parse().unwrap(),
-            split.next().unwrap().parse().unwrap(),
-            split.next().unwrap().parse().unwrap(),
-        )
+    fn from_str(input: &str) -> Result<Self, String> {
+        let coords = input.split(',')
+            .map(|c| c.parse())
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|_| format!("Invalid cube {input:?}"))?;
+        match coords[..] {
+            [x, y, z] => Ok(Cube(x, y, z)),
+            _ => Err(format!("Expected three coordinates in {input:?}")),
+        }
     }
 
     fn add_offset(&self, offset: (i32,i32,i32)) -> Self {
@@ -40,14 +45,30 @@ impl Cube {
     }
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<FxHashSet<Cube>, String> {
+    in_str.lines().map(Cube::from_str).collect()
+}
+
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day18");
-    let cubes: FxHashSet<Cube> = contents.lines().map(Cube::from_str).collect();
+    let cubes = parse_input(&contents).expect("Could not parse input for day18");
     let p1 = part_one(&cubes);
-    let p2 = p1 - part_two(&cubes);
+    let faces = exterior_faces(&cubes);
+    let p2 = faces.len();
 
     println!("Elapsed: {:?}", start.elapsed());
+    // --obj <file> and --stl <file> save the outside of the droplet for a 3D viewer
+    let args: Vec<String> = env::args().collect();
+    let flag_value = |flag: &str| args.iter().position(|arg| arg == flag).map(|i| {
+        args.get(i + 1).unwrap_or_else(|| panic!("{flag} needs a value"))
+    });
+    if let Some(path) = flag_value("--obj") {
+        fs::write(path, mesh::render_obj(&faces)).expect("Could not write the OBJ mesh");
+    }
+    if let Some(path) = flag_value("--stl") {
+        fs::write(path, mesh::render_stl(&faces)).expect("Could not write the STL mesh");
+    }
     println!("D18P1: {p1:?}");
     println!("D18P2: {p2:?}");
 }
@@ -89,43 +110,49 @@ fn in_bounds(Cube(x, y, z): Cube, [x_bounds, y_bounds, z_bounds]: [(i32,i32); 3]
     (z >= z_bounds.0 && z <= z_bounds.1)
 }
 
-fn explore_space(space_cube: Cube, droplet_bounds: [(i32,i32); 3], cubes: &FxHashSet<Cube>) -> Option<FxHashSet<Cube>> {
+// A side of a cube, as the cube and the index of the offset it faces
+type Face = (Cube, usize);
+
+// Floods the air from a corner of the bounding box grown by one, which reaches all the way round the
+// droplet, and collects every face the air touches
+fn exterior_faces(cubes: &FxHashSet<Cube>) -> Vec<Face> {
+    let mut faces = vec!();
+    if cubes.is_empty() {
+        return faces;
+    }
+    let bounds = droplet_bounds(cubes).map(|(low, high)| (low - 1, high + 1));
+    let outside = Cube(bounds[0].0, bounds[1].0, bounds[2].0);
     let mut visited = FxHashSet::default();
     let mut to_visit = VecDeque::new();
-    visited.insert(space_cube);
-    to_visit.push_back(space_cube);
+    visited.insert(outside);
+    to_visit.push_back(outside);
     while let Some(space) = to_visit.pop_front() {
-        if !in_bounds(space, droplet_bounds) {
-            return None
-        }
-
-        for offset in OFFSETS {
+        for (i, offset) in OFFSETS.into_iter().enumerate() {
             let next = space.add_offset(offset);
-            if !visited.contains(&next) && ! cubes.contains(&next){
+            if cubes.contains(&next) {
+                // The face of the cube pointing back at this space
+                faces.push((next, i ^ 1));
+            } else if in_bounds(next, bounds) && visited.insert(next) {
                 to_visit.push_back(next);
-                visited.insert(next);
             }
         }
     }
-    Some(visited)
+    faces
 }
 
-fn part_two(cubes: &FxHashSet<Cube>) -> usize {
-    let mut internal_spaces: FxHashSet<Cube> = FxHashSet::default();
-    let droplet_bounds = droplet_bounds(&cubes);
-
-    for cube in cubes {
-        for offset in OFFSETS {
-            let new_cube = cube.add_offset(offset);
-            if cubes.contains(&new_cube) || internal_spaces.contains(&new_cube) || !in_bounds(new_cube, droplet_bounds) {
-                continue;
-            }
-            if let Some(pocket) = explore_space(new_cube, droplet_bounds, cubes) {
-                pocket.into_iter().for_each(|space| { internal_spaces.insert(space); });
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_surface_area() {
+        let cubes = parse_input(include_str!("../inputs/day18_input_test.txt")).unwrap();
+        assert_eq!(part_one(&cubes), 64);
+        assert_eq!(exterior_faces(&cubes).len(), 58);
+        // Sealing the pocket at 2,2,5 leaves the same outside
+        let mut sealed = cubes.clone();
+        sealed.insert(Cube(2, 2, 5));
+        assert_eq!(part_one(&sealed), 58);
+        assert_eq!(exterior_faces(&sealed).len(), 58);
     }
-
-   part_one(&internal_spaces)
 }
-
@@ -1,7 +1,10 @@
 #![feature(int_roundings)]
 
+use std::env;
 use std::fs;
 use std::cmp::{max, min};
+use std::hash::{Hash, Hasher};
+use std::iter;
 use std::time::Instant;
 
 use lazy_static::lazy_static;
@@ -10,278 +13,506 @@ use rustc_hash::FxHashMap;
 use rayon::prelude::*;
 
 const FILE_PATH: &str = "inputs/day19_input.txt";
+// What the puzzle wants the most of, and the robot we start out with
+const DEFAULT_TARGET: &str = "geode";
+const STARTING_ROBOT: &str = "ore";
+// Amounts live in fixed size arrays so states stay cheap to copy and hash
+const MAX_RESOURCES: usize = 8;
 lazy_static! {
-    static ref BP_RE: Regex = Regex::new(
-        concat!(r"Blueprint (?P<id>\d+): ",
-                r"Each ore robot costs (?P<ore_cost>\d+) ore. ",
-                r"Each clay robot costs (?P<clay_cost>\d+) ore. ",
-                r"Each obsidian robot costs (?P<obs_cost_1>\d+) ore and (?P<obs_cost_2>\d+) clay. ",
-                r"Each geode robot costs (?P<geode_cost_1>\d+) ore and (?P<geode_cost_2>\d+) obsidian."
-        )
-    ).unwrap();
+    static ref BP_RE: Regex = Regex::new(r"^Blueprint (?P<id>\d+):(?P<robots>.*)$").unwrap();
+    static ref ROBOT_RE: Regex = Regex::new(r"^Each (?P<kind>[a-z]+) robot costs (?P<costs>\d+ [a-z]+( and \d+ [a-z]+)*)$").unwrap();
 }
 
+// One amount per resource, indexed the same as Blueprint::resources
+type Amounts = [i32; MAX_RESOURCES];
+
 #[derive(Debug)]
-struct Blueprint {
+pub(crate) struct Blueprint {
     id: i32,
-    // ore
-    ore_robot_cost: i32,
-    // ore
-    clay_robot_cost: i32,
-    // ore, clay
-    obsidian_robot_cost: (i32, i32),
-    // ore, obsidian
-    geode_robot_cost: (i32, i32),
-
-    max_needed_ore: i32,
-    max_needed_clay: i32,
-    max_needed_obsidian: i32,
+    // Every resource the blueprint mentions, in the order they come up
+    resources: Vec<String>,
+    // What the robot collecting each resource costs, if the blueprint has one
+    robot_costs: Vec<Option<Amounts>>,
+    target: usize,
+    start: usize,
+    // The most of each resource any one robot costs, which is all that can be spent in a minute
+    max_needed: Amounts,
 }
 
 impl Blueprint {
-    fn from_str(in_str: &str) -> Self {
-        let captures = BP_RE.captures(in_str).unwrap();
-        let id =  in_str[captures.name("id").unwrap().range()].parse().unwrap();
-        let ore_robot_cost = in_str[captures.name("ore_cost").unwrap().range()].parse().unwrap();
-        let clay_robot_cost = in_str[captures.name("clay_cost").unwrap().range()].parse().unwrap();
-        let obsidian_robot_cost = (
-            in_str[captures.name("obs_cost_1").unwrap().range()].parse().unwrap(),
-            in_str[captures.name("obs_cost_2").unwrap().range()].parse().unwrap(),
-        );
-        let geode_robot_cost = (
-            in_str[captures.name("geode_cost_1").unwrap().range()].parse().unwrap(),
-            in_str[captures.name("geode_cost_2").unwrap().range()].parse().unwrap(),
-        );
-        let max_needed_ore = *[ore_robot_cost, clay_robot_cost, obsidian_robot_cost.0, geode_robot_cost.0].iter().reduce(max).unwrap();
-        let max_needed_clay = obsidian_robot_cost.1;
-        let max_needed_obsidian = geode_robot_cost.1;
-        Blueprint {
-            id,
-            ore_robot_cost,
-            clay_robot_cost,
-            obsidian_robot_cost,
-            geode_robot_cost,        
-            max_needed_ore,
-            max_needed_clay,
-            max_needed_obsidian,
+    fn from_str(in_str: &str, target: &str) -> Result<Self, String> {
+        fn index_of(resources: &mut Vec<String>, name: &str) -> Result<usize, String> {
+            if let Some(i) = resources.iter().position(|r| r == name) {
+                return Ok(i);
+            }
+            if resources.len() == MAX_RESOURCES {
+                return Err(format!("A blueprint can have at most {MAX_RESOURCES} resources"));
+            }
+            resources.push(name.to_string());
+            Ok(resources.len() - 1)
+        }
+
+        let captures = BP_RE.captures(in_str.trim()).ok_or_else(|| format!("Invalid blueprint {in_str:?}"))?;
+        let id = captures["id"].parse().map_err(|_| format!("Invalid id in {in_str:?}"))?;
+        let mut resources = vec!();
+        let mut robots = vec!();
+        for sentence in captures["robots"].split('.').map(str::trim).filter(|s| !s.is_empty()) {
+            let robot = ROBOT_RE.captures(sentence).ok_or_else(|| format!("Invalid robot {sentence:?} in {in_str:?}"))?;
+            let kind = index_of(&mut resources, &robot["kind"])?;
+            let mut costs: Amounts = [0; MAX_RESOURCES];
+            for clause in robot["costs"].split(" and ") {
+                let (amount, name) = clause.split_once(' ').unwrap();
+                let amount: i32 = amount.parse().map_err(|_| format!("Invalid cost {clause:?} in {in_str:?}"))?;
+                let resource = index_of(&mut resources, name)?;
+                costs[resource] = costs[resource].checked_add(amount).ok_or_else(|| format!("Invalid cost {clause:?} in {in_str:?}"))?;
+            }
+            robots.push((kind, costs));
+        }
+
+        let mut robot_costs = vec![None; resources.len()];
+        for (kind, costs) in robots {
+            if robot_costs[kind].replace(costs).is_some() {
+                return Err(format!("Blueprint {id} has two {} robots", resources[kind]));
+            }
+        }
+        let find = |name: &str| resources.iter().position(|r| r == name).ok_or_else(|| format!("Blueprint {id} has no {name}"));
+        let target = find(target)?;
+        let start = find(STARTING_ROBOT)?;
+        let mut max_needed = [0; MAX_RESOURCES];
+        for costs in robot_costs.iter().flatten() {
+            for (needed, &cost) in max_needed.iter_mut().zip(costs) {
+                *needed = max(*needed, cost);
+            }
         }
+        Ok(Blueprint { id, resources, robot_costs, target, start, max_needed })
     }
 }
 
-#[derive(Hash, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 struct State {
-    ore: i32,
-    clay: i32,
-    obsidian: i32,
-    ore_bots: i32,
-    clay_bots: i32,
-    obsidian_bots: i32,
-    geode_bots: i32,
+    stock: Amounts,
+    robots: Amounts,
+    // How many resources the blueprint has, the rest of the slots are always 0
+    len: usize,
+}
+
+// Only the slots the blueprint uses, as hashing is most of what the search does
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.stock[..self.len].hash(state);
+        self.robots[..self.len].hash(state);
+    }
 }
 
 impl State {
-    fn new() -> Self {
-        State {
-            ore: 0,
-            clay: 0,
-            obsidian: 0,
-            ore_bots: 1,
-            clay_bots: 0,
-            obsidian_bots: 0,
-            geode_bots: 0,
-        }
+    fn new(bp: &Blueprint) -> Self {
+        let mut robots = [0; MAX_RESOURCES];
+        robots[bp.start] = 1;
+        State { stock: [0; MAX_RESOURCES], robots, len: bp.resources.len() }
     }
 
     fn iterate(&self, ticks: i32) -> Self {
-        let State { clay_bots, obsidian_bots, ore_bots, .. } = self;
-        let mut next = self.clone(); 
-        next.ore += ore_bots * ticks;
-        next.obsidian += obsidian_bots * ticks;
-        next.clay += clay_bots * ticks;
-
+        let mut next = *self;
+        for (stock, robots) in next.stock.iter_mut().zip(self.robots) {
+            *stock += robots * ticks;
+        }
         next
     }
 
-    fn can_sustain_geode_bot_production(&self, bp: &Blueprint) -> bool {
-        let (ore, obsidian) = bp.geode_robot_cost;
-        self.ore_bots >= ore && self.obsidian_bots >= obsidian
-    }
-
-    fn can_sustain_ore_production(&self, bp: &Blueprint) -> bool {
-        self.ore_bots >= bp.max_needed_ore
-    }
-
-    fn can_sustain_clay_production(&self, bp: &Blueprint) -> bool {
-        self.clay_bots >= bp.max_needed_clay
+    // We can build a target robot now and every minute after that. That's only the best there is
+    // when building them doesn't use up the target itself
+    fn can_sustain_target_production(&self, bp: &Blueprint) -> bool {
+        let Some(costs) = bp.robot_costs[bp.target] else {
+            return false;
+        };
+        costs[bp.target] == 0 && (0..bp.resources.len()).all(|r| self.robots[r] >= costs[r] && self.stock[r] >= costs[r])
     }
 
-    fn can_sustain_obsidian_production(&self, bp: &Blueprint) -> bool {
-        self.obsidian_bots >= bp.max_needed_obsidian
+    fn can_sustain_production(&self, bp: &Blueprint, resource: usize) -> bool {
+        self.robots[resource] >= bp.max_needed[resource]
     }
 
-    fn ticks_until_ore_bot(&self, bp: &Blueprint) -> i32 {
-        if self.ore >= bp.ore_robot_cost {
-            return 1
+    // Anything beyond what could be spent in the time left makes no difference, so forgetting about
+    // it lets far more states share a cache entry
+    fn discard_unspendable(&mut self, bp: &Blueprint, time: i32) {
+        for (stock, needed) in self.stock.iter_mut().zip(bp.max_needed) {
+            *stock = min(*stock, needed * time);
         }
-        return 1 + (bp.ore_robot_cost - self.ore).div_ceil(self.ore_bots);
     }
 
-    fn ticks_until_clay_bot(&self, bp: &Blueprint) -> i32 {
-        if self.ore >= bp.clay_robot_cost {
-            return 1;
+    // Minutes until a robot can be ready, waiting for whatever it costs first. None if some of that
+    // is never going to be collected
+    fn ticks_until(&self, bp: &Blueprint, robot: usize) -> Option<i32> {
+        let costs = bp.robot_costs[robot]?;
+        let mut wait = 0;
+        for ((cost, stock), robots) in costs.into_iter().zip(self.stock).zip(self.robots) {
+            if stock >= cost {
+                continue;
+            }
+            if robots == 0 {
+                return None;
+            }
+            wait = max(wait, (cost - stock).div_ceil(robots));
         }
+        Some(1 + wait)
+    }
 
-        return 1 + (bp.clay_robot_cost - self.ore).div_ceil(self.ore_bots);
+    fn worth_building(&self, bp: &Blueprint, robot: usize) -> bool {
+        robot == bp.target || !self.can_sustain_production(bp, robot)
     }
 
-    fn ticks_until_obsidian_bot(&self, bp: &Blueprint) -> Option<i32> {
-        let (ore_cost, clay_cost) = bp.obsidian_robot_cost;
-        if self.ore >= ore_cost && self.clay >= clay_cost {
-            return Some(1);
+    // How many minutes until `robot` is ready along with the state after it is, if it ever can be
+    fn build(&self, bp: &Blueprint, robot: usize) -> Option<(i32, State)> {
+        let ticks = self.ticks_until(bp, robot)?;
+        let mut next = self.iterate(ticks);
+        for (stock, cost) in next.stock.iter_mut().zip(bp.robot_costs[robot].unwrap()) {
+            *stock -= cost;
         }
+        next.robots[robot] += 1;
+        Some((ticks, next))
+    }
+}
 
-        if self.clay_bots == 0 {
-            return None;
-        }
+// A robot getting built, and how things stand at the end of that minute
+#[derive(Debug)]
+struct Step {
+    minute: i32,
+    robot: usize,
+    state: State,
+    // How much of the target has been collected, less any spent
+    collected: i32,
+}
 
-        return Some(1 + max((ore_cost - self.ore).div_ceil(self.ore_bots), (clay_cost - self.clay).div_ceil(self.clay_bots)))
+impl Step {
+    fn describe(&self, bp: &Blueprint) -> String {
+        let stock: Vec<String> = (0..bp.resources.len())
+            .filter(|&r| r != bp.target)
+            .map(|r| format!("{} {}", self.state.stock[r], bp.resources[r]))
+            .collect();
+        let robots: Vec<String> = (0..bp.resources.len()).map(|r| self.state.robots[r].to_string()).collect();
+        format!(
+            "Minute {:>2}: {} robot built, stock {}, {} {} so far, robots {}",
+            self.minute,
+            bp.resources[self.robot],
+            stock.join(", "),
+            self.collected,
+            bp.resources[bp.target],
+            robots.join("/"),
+        )
     }
+}
 
-    fn ticks_until_geode_bot(&self, bp: &Blueprint) -> Option<i32> {
-        let (ore_cost, obsidian_cost) = bp.geode_robot_cost;
-        if self.ore >= ore_cost && self.obsidian >= obsidian_cost {
-            return Some(1);
-        }
-
-        if self.obsidian_bots == 0 {
-            return None;
-        }
+/// Reads one blueprint a line. Each robot collects the resource it's named after and can cost any
+/// number of resources, like "Each geode robot costs 2 ore and 7 obsidian."
+pub(crate) fn parse_blueprints(in_str: &str, target: &str) -> Result<Vec<Blueprint>, String> {
+    in_str.lines().map(|line| Blueprint::from_str(line, target)).collect()
+}
 
-        return Some(1 + max((ore_cost - self.ore).div_ceil(self.ore_bots), (obsidian_cost - self.obsidian).div_ceil(self.obsidian_bots)))
-    }
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Blueprint>, String> {
+    parse_blueprints(in_str, DEFAULT_TARGET)
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day19");
-    let blueprints = contents.lines().map(Blueprint::from_str).collect();
-    let p1 = part_one(&blueprints);
+    let args: Vec<String> = env::args().collect();
+    // --target <resource> maximises something other than geodes
+    let blueprints = match args.iter().position(|arg| arg == "--target") {
+        Some(i) => parse_blueprints(&contents, args.get(i + 1).expect("--target needs a value")),
+        None => parse_input(&contents),
+    }.expect("Could not parse input for day19");
+    let (p1, plans) = part_one(&blueprints);
     let p2 = part_two(&blueprints);
 
     println!("Elapsed: {:?}", start.elapsed());
+    // --plans shows how each blueprint gets its result in part one, played out to check it
+    if args.iter().any(|arg| arg == "--plans") {
+        for (bp, (collected, steps)) in blueprints.iter().zip(plans) {
+            let replayed = match replay(bp, 24, &steps) {
+                Ok(replayed) if replayed == collected => "checks out".to_string(),
+                Ok(replayed) => format!("but playing it out gives {replayed}"),
+                Err(error) => format!("but it can't be played out: {error}"),
+            };
+            println!("Blueprint {}: {collected} {} collected, {replayed}", bp.id, bp.resources[bp.target]);
+            steps.iter().for_each(|step| println!("  {}", step.describe(bp)));
+        }
+    }
     println!("D19P1: {p1:?}");
     println!("D19P2: {p2:?}");
 }
 
-fn max_possible_geodes(t: i32, geode_bots: i32) -> i32 {
-    (0..t).fold(0, |acc, i| acc + ((t - i) * (geode_bots + i)))
+// Building a target robot every minute is the best that could ever happen
+fn max_possible(t: i32, target_bots: i32) -> i32 {
+    (0..t).fold(0, |acc, i| acc + target_bots + i)
 }
 
-fn get_quality_level(bp: &Blueprint, time: i32) -> i32 {
-    type Cache = FxHashMap<(i32, State), i32>;
-    type MaxGeodeCache = FxHashMap<i32, i32>;
-    fn helper(time: i32, state: State, cache: &mut Cache, max_geode_cache: &mut MaxGeodeCache, bp: &Blueprint) -> i32 {
-        if time <= 0 {
-            return 0;
+#[derive(Debug, Clone, Copy)]
+enum Cached {
+    // The most of the target there is to collect, and the robot to build next for it
+    Exact(i32, Option<usize>),
+    // The search gave up once it was clear there's no more than this to collect
+    AtMost(i32),
+}
+
+type Cache = FxHashMap<(i32, State), Cached>;
+
+// The most of the target that can be collected in the time left, as long as that's more than
+// `to_beat`. Otherwise it gives up early, and only promises an answer no more than `to_beat`
+fn search(time: i32, mut state: State, to_beat: i32, cache: &mut Cache, bp: &Blueprint) -> i32 {
+    if time <= 0 {
+        return 0;
+    }
+    state.discard_unspendable(bp, time);
+    let cache_key = (time, state);
+    match cache.get(&cache_key) {
+        Some(&Cached::Exact(cached, _)) => return cached,
+        Some(&Cached::AtMost(cached)) if cached <= to_beat => return cached,
+        _ => (),
+    }
+
+    let max_possible = max_possible(time, state.robots[bp.target]);
+    if state.can_sustain_target_production(bp) {
+        cache.insert(cache_key, Cached::Exact(max_possible, Some(bp.target)));
+        return max_possible;
+    }
+    if max_possible <= to_beat {
+        return max_possible;
+    }
+
+    // Building nothing more and collecting until the end
+    let mut best = (time * state.robots[bp.target], None);
+    // Target robots first, as they find good answers soonest and so cut the rest of the search short
+    let robots = iter::once(bp.target).chain((0..bp.resources.len()).filter(|&r| r != bp.target));
+    for robot in robots {
+        if !state.worth_building(bp, robot) {
+            continue;
         }
-        let cache_key = (time, state);
-        if let Some(&cached) = cache.get(&cache_key) {
-            return cached
+        // A robot that isn't ready in time is no better than waiting
+        let Some((ticks, next_state)) = state.build(bp, robot).filter(|&(ticks, _)| ticks <= time) else {
+            continue;
+        };
+        let collected = ticks * state.robots[bp.target] - bp.robot_costs[robot].unwrap()[bp.target];
+        let total = collected + search(time - ticks, next_state, max(to_beat, best.0) - collected, cache, bp);
+        if total > best.0 {
+            best = (total, Some(robot));
         }
+    }
+
+    if best.0 > to_beat {
+        cache.insert(cache_key, Cached::Exact(best.0, best.1));
+        best.0
+    } else {
+        cache.insert(cache_key, Cached::AtMost(to_beat));
+        to_beat
+    }
+}
+
+fn get_quality_level(bp: &Blueprint, time: i32) -> i32 {
+    // Never collecting anything at all is always possible
+    search(time, State::new(bp), -1, &mut FxHashMap::default(), bp)
+}
 
-        let max_possible = max_possible_geodes(time, state.geode_bots);
-        if let Some(&cached) = max_geode_cache.get(&time) {
-            if cached > max_possible {
-                cache.insert(cache_key, 0);
-                return 0;
+// Searches once, then follows the choices the search settled on to find which robot gets built when
+fn build_order(bp: &Blueprint, time: i32) -> (i32, Vec<Step>) {
+    let mut cache = FxHashMap::default();
+    let most = search(time, State::new(bp), -1, &mut cache, bp);
+    let mut steps = vec!();
+    let (mut state, mut time_left, mut collected) = (State::new(bp), time, 0);
+    while time_left > 0 {
+        let mut key = state;
+        key.discard_unspendable(bp, time_left);
+        // The search stops looking once a target robot can be built every minute
+        let choice = if key.can_sustain_target_production(bp) {
+            Some(bp.target)
+        } else {
+            // Every state on the best path was searched to the end
+            match cache.get(&(time_left, key)) {
+                Some(&Cached::Exact(_, robot)) => robot,
+                _ => unreachable!("The best path leads to a state that wasn't searched"),
             }
+        };
+        let Some(robot) = choice else {
+            break;
+        };
+        let (ticks, next_state) = state.build(bp, robot).unwrap();
+        collected += ticks * state.robots[bp.target] - bp.robot_costs[robot].unwrap()[bp.target];
+        steps.push(Step { minute: time - time_left + ticks, robot, state: next_state, collected });
+        state = next_state;
+        time_left -= ticks;
+    }
+    (most, steps)
+}
+
+// Plays the build order out a minute at a time, checking each robot can be paid for when it's
+// started, and counts what's collected of the target
+fn replay(bp: &Blueprint, time: i32, steps: &[Step]) -> Result<i32, String> {
+    let (mut state, mut collected) = (State::new(bp), 0);
+    let mut steps = steps.iter().peekable();
+    for minute in 1..=time {
+        let built = steps.next_if(|step| step.minute == minute).map(|step| step.robot);
+        let costs = built.map_or([0; MAX_RESOURCES], |robot| bp.robot_costs[robot].unwrap());
+        if (0..bp.resources.len()).any(|r| state.stock[r] < costs[r]) {
+            return Err(format!("Can't afford the {} robot in minute {minute}", bp.resources[built.unwrap()]));
         }
-        
-        if state.can_sustain_geode_bot_production(bp) {
-            cache.insert(cache_key, max_possible);
-            max_geode_cache.insert(time, max_possible);
-            return max_possible;
+        collected += state.robots[bp.target] - costs[bp.target];
+        let mut next = state.iterate(1);
+        for (stock, cost) in next.stock.iter_mut().zip(costs) {
+            *stock -= cost;
         }
-
-        // It is always better to build a geode robot if you can do so
-        if bp.geode_robot_cost.1 <= state.ore && bp.geode_robot_cost.1 <= state.obsidian {
-            let mut next_state = state.iterate(1);
-            next_state.ore -= bp.geode_robot_cost.0;
-            next_state.obsidian -= bp.geode_robot_cost.1;
-            next_state.geode_bots += 1;
-            let mut res = helper(time - 1, next_state, cache, max_geode_cache, bp);
-            res += state.geode_bots;
-            cache.insert(cache_key, res);
-            if let Some(max_geodes) = max_geode_cache.get_mut(&time) {
-                *max_geodes = max(*max_geodes, res);
-            } else {
-                max_geode_cache.insert(time, res);
-            }
-            return res;
-        } 
-
-        let mut res = 0;
-        if !state.can_sustain_ore_production(bp) {
-            let ticks = state.ticks_until_ore_bot(bp);
-            let mut next_state = state.iterate(ticks);
-            next_state.ore -= bp.ore_robot_cost;
-            next_state.ore_bots += 1;
-            let next_time = time - ticks;
-            let generated = min(ticks, time) * state.geode_bots;
-            res = max(res, generated + helper(next_time, next_state, cache, max_geode_cache, bp));
+        if let Some(robot) = built {
+            next.robots[robot] += 1;
         }
+        state = next;
+    }
+    match steps.next() {
+        Some(step) => Err(format!("The {} robot in minute {} is out of order", bp.resources[step.robot], step.minute)),
+        None => Ok(collected),
+    }
+}
 
-        if !state.can_sustain_clay_production(bp) {
-            let ticks = state.ticks_until_clay_bot(bp);
-            let mut next_state = state.iterate(ticks);
-            let next_time = time - ticks;
-            next_state.ore -= bp.clay_robot_cost;
-            next_state.clay_bots += 1;
-            let generated = min(ticks, time) * state.geode_bots;
-            res = max(res, generated + helper(next_time, next_state, cache, max_geode_cache, bp));
-        }
+// The build orders come along for free, since the search has already made every choice in them
+fn part_one(blueprints: &Vec<Blueprint>) -> (i32, Vec<(i32, Vec<Step>)>) {
+    let plans: Vec<(i32, Vec<Step>)> = blueprints.par_iter().map(|bp| build_order(bp, 24)).collect();
+    let quality = blueprints.iter().zip(plans.iter()).map(|(bp, (collected, _))| bp.id * collected).sum();
+    (quality, plans)
+}
+
+fn part_two(blueprints: &Vec<Blueprint>) -> i32 {
+    blueprints[..3].par_iter().map(|bp|get_quality_level(bp, 32)).reduce(||1, |acc, val| acc * val)
+}
 
-        if !state.can_sustain_obsidian_production(bp) {
-            if let Some(ticks) = state.ticks_until_obsidian_bot(bp) {
-                let mut next_state = state.iterate(ticks);
-                let (ore_cost, clay_cost) = bp.obsidian_robot_cost;
-                let next_time = time - ticks;
-                next_state.ore -= ore_cost;
-                next_state.clay -= clay_cost;
-                next_state.obsidian_bots += 1;
-                let generated = min(ticks, time) * state.geode_bots;
-                res = max(res, generated + helper(next_time, next_state, cache, max_geode_cache, bp));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rustc_hash::FxHashSet;
+
+    // Tries doing nothing and building every affordable robot, every minute. The only shortcuts are
+    // never owning more robots than could be spent on in a minute, and forgetting about stock that
+    // could never be spent in the time left
+    fn most_collected(bp: &Blueprint, time: i32) -> i32 {
+        let mut states = FxHashSet::default();
+        states.insert((State::new(bp), 0));
+        for time_remaining in (1..=time).rev() {
+            let mut next_states = FxHashSet::default();
+            let mut add = |mut state: State, collected: i32| {
+                state.discard_unspendable(bp, time_remaining - 1);
+                next_states.insert((state, collected));
+            };
+            for &(state, collected) in states.iter() {
+                let gathered = state.iterate(1);
+                let collected = collected + state.robots[bp.target];
+                add(gathered, collected);
+                for robot in 0..bp.resources.len() {
+                    let Some(costs) = bp.robot_costs[robot] else {
+                        continue;
+                    };
+                    let affordable = (0..bp.resources.len()).all(|r| state.stock[r] >= costs[r]);
+                    if affordable && (robot == bp.target || state.robots[robot] < bp.max_needed[robot]) {
+                        let mut next = gathered;
+                        for (stock, cost) in next.stock.iter_mut().zip(costs) {
+                            *stock -= cost;
+                        }
+                        next.robots[robot] += 1;
+                        add(next, collected - costs[bp.target]);
+                    }
+                }
             }
+            states = next_states;
         }
+        states.into_iter().map(|(_, collected)| collected).max().unwrap()
+    }
 
-        if let Some(ticks) = state.ticks_until_geode_bot(bp) {
-            let mut next_state = state.iterate(ticks);
-            let (ore_cost, obsidian_cost) = bp.geode_robot_cost;
-            let next_time = time - ticks;
-            next_state.ore -= ore_cost;
-            next_state.obsidian -= obsidian_cost;
-            next_state.geode_bots += 1;
-            let generated = min(ticks, time) * state.geode_bots;
-            res = max(res, generated + helper(next_time, next_state, cache, max_geode_cache, bp));
-        }
+    fn blueprint() -> impl Strategy<Value = String> {
+        (1..=4, 1..=4, 1..=4, 1..=8, 1..=4, 1..=8).prop_map(|(ore, clay, obs_ore, obs_clay, geode_ore, geode_obs)| {
+            format!(concat!(
+                "Blueprint 1: Each ore robot costs {} ore. Each clay robot costs {} ore. ",
+                "Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian."
+            ), ore, clay, obs_ore, obs_clay, geode_ore, geode_obs)
+        })
+    }
 
+    // Two to four resources, the last of them the target, with robots that can cost anything at all
+    // including the target
+    fn any_blueprint() -> impl Strategy<Value = (String, &'static str)> {
+        const NAMES: [&str; 4] = ["ore", "clay", "obsidian", "geode"];
+        (2usize..=4)
+            .prop_flat_map(|n| prop::collection::vec(prop::collection::vec(0..=3, n), n))
+            .prop_map(|costs| {
+                let robots: Vec<String> = costs.iter().enumerate()
+                    .map(|(robot, costs)| {
+                        let mut clauses: Vec<String> = costs.iter().enumerate()
+                            .filter(|&(_, &cost)| cost > 0)
+                            .map(|(r, cost)| format!("{cost} {}", NAMES[r]))
+                            .collect();
+                        if clauses.is_empty() {
+                            clauses.push("1 ore".to_string());
+                        }
+                        format!("Each {} robot costs {}.", NAMES[robot], clauses.join(" and "))
+                    })
+                    .collect();
+                (format!("Blueprint 1: {}", robots.join(" ")), NAMES[costs.len() - 1])
+            })
+    }
 
-        cache.insert(cache_key, res);
-        if let Some(max_geodes) = max_geode_cache.get_mut(&time) {
-            *max_geodes = max(*max_geodes, res);
-        } else {
-            max_geode_cache.insert(time, res);
+    #[test]
+    fn parses_any_costs() {
+        let bp = Blueprint::from_str(
+            "Blueprint 7: Each ore robot costs 2 ore. Each crystal robot costs 1 ore and 3 dust and 2 geode. Each geode robot costs 5 ore.",
+            "geode",
+        ).unwrap();
+        assert_eq!(bp.id, 7);
+        assert_eq!(bp.resources, ["ore", "crystal", "dust", "geode"]);
+        assert_eq!(bp.robot_costs[1].unwrap()[..4], [1, 0, 3, 2]);
+        // Nothing collects dust, so crystal robots can never be built
+        assert_eq!(bp.robot_costs[2], None);
+        assert_eq!(bp.max_needed[..4], [5, 0, 3, 2]);
+        assert_eq!((bp.target, bp.start), (3, 0));
+
+        assert!(Blueprint::from_str("Blueprint 1: Each ore robot costs 2 ore.", "geode").is_err());
+        assert!(Blueprint::from_str("Blueprint 1: Each clay robot costs 2 clay.", "clay").is_err());
+        assert!(Blueprint::from_str("Blueprint 1: Each ore robot costs 2 ore. Each ore robot costs 1 ore.", "ore").is_err());
+        assert!(Blueprint::from_str("Blueprint 1: Each ore robot costs two ore.", "ore").is_err());
+        let too_many: Vec<String> = (b'a'..=b'i').map(|c| format!("Each {} robot costs 1 ore.", c as char)).collect();
+        assert!(Blueprint::from_str(&format!("Blueprint 1: Each ore robot costs 1 ore. {}", too_many.join(" ")), "ore").is_err());
+    }
+
+    #[test]
+    fn sample_build_orders() {
+        let blueprints = parse_input(include_str!("../inputs/day19_input_test.txt")).unwrap();
+        for (bp, expected) in blueprints.iter().zip([9, 12]) {
+            let (geodes, steps) = build_order(bp, 24);
+            assert_eq!(geodes, expected);
+            assert_eq!(replay(bp, 24, &steps), Ok(expected));
+            // The last robot's geodes only come in after it's built
+            let last = steps.last().unwrap();
+            assert_eq!(last.collected + last.state.robots[bp.target] * (24 - last.minute), expected);
         }
-        res
+        // The first geode robot can't be built any sooner
+        let (_, mut steps) = build_order(&blueprints[0], 24);
+        let geode_step = steps.iter().position(|step| step.robot == blueprints[0].target).unwrap();
+        steps[geode_step].minute -= 1;
+        assert!(replay(&blueprints[0], 24, &steps).is_err());
     }
-    let res = helper(time, State::new(), &mut FxHashMap::default(), &mut FxHashMap::default(), bp);
-    return res
-}
 
-fn part_one(blueprints: &Vec<Blueprint>) -> i32 {
-    blueprints.par_iter().map(|bp|bp.id * get_quality_level(bp, 24)).sum()
-}
+    proptest! {
+        // The reference slows down quickly as time goes on
+        #![proptest_config(ProptestConfig::with_cases(64))]
 
-fn part_two(blueprints: &Vec<Blueprint>) -> i32 {
-    blueprints[..3].par_iter().map(|bp|get_quality_level(bp, 32)).reduce(||1, |acc, val| acc * val)
+        #[test]
+        fn search_matches_every_choice(blueprint in blueprint(), time in 1..=20) {
+            let bp = Blueprint::from_str(&blueprint, DEFAULT_TARGET).unwrap();
+            let (geodes, steps) = build_order(&bp, time);
+            prop_assert_eq!(geodes, most_collected(&bp, time));
+            prop_assert_eq!(replay(&bp, time, &steps), Ok(geodes));
+        }
+
+        #[test]
+        fn any_blueprint_matches_every_choice((blueprint, target) in any_blueprint(), time in 1..=14) {
+            let bp = Blueprint::from_str(&blueprint, target).unwrap();
+            let (collected, steps) = build_order(&bp, time);
+            prop_assert_eq!(collected, most_collected(&bp, time));
+            prop_assert_eq!(replay(&bp, time, &steps), Ok(collected));
+        }
+    }
 }
@@ -6,14 +6,25 @@ const FILE_PATH: &str = "inputs/day2_input.txt";
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day2");
-    let parsed = contents.trim().lines();
-    let p1 = evaluate(parsed.clone(), get_p1_score);
-    let p2 = evaluate(parsed, get_p2_score);
+    let parsed = parse_input(&contents).expect("Could not parse input for day2");
+    let p1 = evaluate(&parsed, get_p1_score);
+    let p2 = evaluate(&parsed, get_p2_score);
     println!("Elapsed: {:?}", start.elapsed());
     println!("D2P1: {p1:?}");
     println!("D2P2: {p2:?}");
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<(&str, &str)>, String> {
+    in_str
+        .trim()
+        .lines()
+        .map(|line| match line.split_once(' ') {
+            Some((play @ ("A" | "B" | "C"), response @ ("X" | "Y" | "Z"))) => Ok((play, response)),
+            _ => Err(format!("Invalid round {line:?}")),
+        })
+        .collect()
+}
+
 fn get_p1_score(a: &str, b: &str) -> i32 {
     match b {
         "X" => 1 + match a {
@@ -91,12 +102,9 @@ fn get_p2_score(play: &str, outcome: &str) -> i32 {
 
 }
 
-fn evaluate<'a>(plays: impl Iterator<Item = &'a str>, score_func: fn(&'a str, &'a str) -> i32) -> i32 {
+fn evaluate<'a>(plays: &[(&'a str, &'a str)], score_func: fn(&'a str, &'a str) -> i32) -> i32 {
     let mut total = 0;
-    for line in plays {
-        let mut split = line.split(' ');
-        let play = split.next().unwrap();
-        let response = split.next().unwrap();
+    for &(play, response) in plays {
         total += score_func(play, response);
     }
     total
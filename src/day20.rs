@@ -4,13 +4,24 @@ use std::time::Instant;
 const FILE_PATH: &str = "inputs/day20_input.txt";
 const SCALE_VAL: i64 = 811589153;
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<i64>, String> {
+    let values = in_str
+        .lines()
+        .map(|v| v.parse().map_err(|_| format!("Invalid number {v:?}")))
+        .collect::<Result<Vec<i64>, _>>()?;
+    if values.iter().filter(|&&v| v == 0).count() != 1 {
+        return Err("The file must contain exactly one zero".to_string());
+    }
+    Ok(values)
+}
+
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day20");
-    let mut p1_inputs: Vec<(usize, i64)> = contents
-        .lines()
+    let mut p1_inputs: Vec<(usize, i64)> = parse_input(&contents)
+        .expect("Could not parse input for day20")
+        .into_iter()
         .enumerate()
-        .map(|(i,v)| (i, v.parse().unwrap()))
         .collect();
 
     let p1 = mix(&mut p1_inputs.clone(), 1, 1);
@@ -34,3 +45,48 @@ fn mix(grove_coords: &mut Vec<(usize, i64)>, scale: i64, num_mixes: i32) -> i64
     let zero_index = grove_coords.iter().position(|&(_, val)| val == 0).unwrap();
     [1000, 2000, 3000].iter().map(|&v| grove_coords[(zero_index + v as usize) % len].1 * scale).sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Moves every number one swap at a time, so it never needs to reason about wrapping
+    fn mix_by_swapping(values: &[i64], scale: i64, num_mixes: i32) -> i64 {
+        let len = values.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        for _ in 0..num_mixes {
+            for (i, value) in values.iter().enumerate() {
+                let mut index = order.iter().position(|&v| v == i).unwrap();
+                let steps = value * scale;
+                for _ in 0..steps.abs() {
+                    let next_index = if steps > 0 { (index + 1) % len } else { (index + len - 1) % len };
+                    order.swap(index, next_index);
+                    index = next_index;
+                }
+            }
+        }
+        let zero_index = order.iter().position(|&i| values[i] == 0).unwrap();
+        [1000, 2000, 3000].iter().map(|&v| values[order[(zero_index + v) % len]] * scale).sum()
+    }
+
+    fn file() -> impl Strategy<Value = Vec<i64>> {
+        (prop::collection::vec(-30i64..=30, 1..12), any::<prop::sample::Index>())
+            .prop_map(|(mut values, zero)| {
+                for value in values.iter_mut().filter(|v| **v == 0) {
+                    *value = 1;
+                }
+                let zero = zero.index(values.len() + 1);
+                values.insert(zero, 0);
+                values
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn mix_matches_swapping(values in file(), scale in 1i64..=5, num_mixes in 1i32..=3) {
+            let mut grove_coords: Vec<(usize, i64)> = values.iter().copied().enumerate().collect();
+            prop_assert_eq!(mix(&mut grove_coords, scale, num_mixes), mix_by_swapping(&values, scale, num_mixes));
+        }
+    }
+}
@@ -13,7 +13,7 @@ lazy_static! {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum Operator {
+pub(crate) enum Operator {
     Add,
     Subtract,
     Divide,
@@ -64,34 +64,47 @@ impl std::fmt::Display for Operator {
 }
 
 #[derive(Debug)]
-enum Monkey<'a> {
+pub(crate) enum Monkey<'a> {
     Number(i64),
     Equation(&'a str, Operator, &'a str),
 }
 
 impl<'a> Monkey<'a> {
-    fn from_str(in_str: &'a str) -> Self {
-        let captures = MONKEY_REGEX.captures(in_str).unwrap();
+    fn from_str(in_str: &'a str) -> Result<(&'a str, Self), String> {
+        let captures = MONKEY_REGEX.captures(in_str).ok_or_else(|| format!("Invalid monkey {in_str:?}"))?;
+        let name = &in_str[captures.name("name").unwrap().range()];
         if let Some(num) = captures.name("number") {
-            return Monkey::Number(in_str[num.range()].parse().unwrap());
+            let num = in_str[num.range()].parse().map_err(|_| format!("Invalid number in {in_str:?}"))?;
+            return Ok((name, Monkey::Number(num)));
         }
 
         let lhs = &in_str[captures.name("lhs").unwrap().range()];
         let rhs = &in_str[captures.name("rhs").unwrap().range()];
         let operator = Operator::from_str(&in_str[captures.name("operator").unwrap().range()]);
-        Monkey::Equation(lhs, operator, rhs)
+        Ok((name, Monkey::Equation(lhs, operator, rhs)))
     }
 }
 
-fn main() {
-    let start = Instant::now();
-    let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day21");
+pub(crate) fn parse_input(in_str: &str) -> Result<FxHashMap<&str, Monkey<'_>>, String> {
     let mut monkeys = FxHashMap::default();
-    for line in contents.lines() {
-        let (monkey_name, _) = line.split_once(':').unwrap();
-        let monkey = Monkey::from_str(line);
+    for line in in_str.lines() {
+        let (monkey_name, monkey) = Monkey::from_str(line)?;
         monkeys.insert(monkey_name, monkey);
     }
+    for monkey in monkeys.values() {
+        if let Monkey::Equation(lhs, _, rhs) = monkey {
+            if let Some(name) = [lhs, rhs].into_iter().find(|&name| !monkeys.contains_key(name)) {
+                return Err(format!("Unknown monkey {name:?}"));
+            }
+        }
+    }
+    Ok(monkeys)
+}
+
+fn main() {
+    let start = Instant::now();
+    let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day21");
+    let monkeys = parse_input(&contents).expect("Could not parse input for day21");
     let p1 = part_one(&monkeys);
     let p2 = part_two(&monkeys);
     println!("Elapsed: {:?}", start.elapsed());
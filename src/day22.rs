@@ -26,7 +26,7 @@ impl Face {
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum MapSpace {
+pub(crate) enum MapSpace {
     Wall,
     Open,
     Unavailable,
@@ -87,33 +87,36 @@ impl Orientation {
 }
 
 #[derive(Debug)]
-enum Direction {
+pub(crate) enum Direction {
     Walk(u32),
     Turn(Turn),
 }
 
 #[derive(Debug, Copy, Clone)]
-enum Turn {
+pub(crate) enum Turn {
     Right = 1,
     Left = -1,
 }
 
 impl Turn {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Result<Self, String> {
         match c {
-            'L' => Turn::Left,
-            'R' => Turn::Right,
-            _ => unreachable!(),
+            'L' => Ok(Turn::Left),
+            'R' => Ok(Turn::Right),
+            _ => Err(format!("Unknown turn {c:?}")),
         }
     }
 }
 
-fn parse_map(in_str: &str) -> Map {
+fn parse_map(in_str: &str) -> Result<Map, String> {
     let mut map = [[MapSpace::Unavailable; MAP_WIDTH]; MAP_HEIGHT];
     for (i, line) in in_str.lines().enumerate() {
-        line.chars().enumerate().for_each(|(j, c)| map[i][j] = MapSpace::from_char(c));
+        let row = map.get_mut(i).ok_or("The map is too tall")?;
+        for (j, c) in line.chars().enumerate() {
+            *row.get_mut(j).ok_or("The map is too wide")? = MapSpace::from_char(c);
+        }
     }
-    map
+    Ok(map)
 }
 
 fn parse_cube(map: Map) -> Cube {
@@ -245,31 +248,33 @@ fn parse_cube(map: Map) -> Cube {
     cube
 }
 
-fn parse_directions(in_str: &str) -> Vec<Direction> {
+fn parse_directions(in_str: &str) -> Result<Vec<Direction>, String> {
     let mut out = vec!();
     let mut iter = in_str.chars().peekable();
     while let Some(c) = iter.next() {
-        if c.is_ascii_digit() {
-            let mut val = c.to_digit(10).unwrap();
-            while iter.peek().is_some() && iter.peek().unwrap().is_ascii_digit() {
-                val *= 10;
-                val += iter.next().unwrap().to_digit(10).unwrap();
+        if let Some(mut val) = c.to_digit(10) {
+            while let Some(digit) = iter.peek().and_then(|d| d.to_digit(10)) {
+                iter.next();
+                val = val.checked_mul(10).and_then(|v| v.checked_add(digit)).ok_or("Walk distance is too large")?;
             }
             out.push(Direction::Walk(val));
         } else {
-            out.push(Direction::Turn(Turn::from_char(c)));
+            out.push(Direction::Turn(Turn::from_char(c)?));
         }
     }
-    out
+    Ok(out)
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<(Map, Vec<Direction>), String> {
+    let (raw_map, raw_directions) = in_str.trim_end().split_once("\n\n").ok_or("Missing directions")?;
+    Ok((parse_map(raw_map)?, parse_directions(raw_directions)?))
 }
 
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day22");
-    let (raw_map, raw_directions) = contents.trim_end().split_once("\n\n").unwrap();
-    let map = parse_map(raw_map);
+    let (map, directions) = parse_input(&contents).expect("Could not parse input for day22");
     let cube = parse_cube(map);
-    let directions = parse_directions(raw_directions);
     let p1 = walk(&cube, &directions, true);
     let p2 = walk(&cube, &directions, false);
     println!("Elapsed: {:?}", start.elapsed());
@@ -41,26 +41,33 @@ impl Direction {
     }
 }
 
-fn parse_elf_positions(input: &str, position_store: &mut [[bool; ELF_POSITION_BOUND]; ELF_POSITION_BOUND]) -> Vec<(i32, i32)> {
+pub(crate) fn parse_input(input: &str) -> Result<Vec<(i32, i32)>, String> {
     let mut out = vec!();
     for (i, line) in input.lines().enumerate() {
         for (j, char) in line.chars().enumerate() {
             match char {
                 '#' => {
-                    position_store[i + OFFSET as usize][j + OFFSET as usize] = true;
+                    if i + OFFSET as usize >= ELF_POSITION_BOUND || j + OFFSET as usize >= ELF_POSITION_BOUND {
+                        return Err(format!("Elf at {i},{j} is too far out to track"));
+                    }
                     out.push((i as i32, j as i32));
                 },
-                _ => continue,
+                '.' => continue,
+                _ => return Err(format!("Unexpected tile {char:?}")),
             };
         }
     }
-    out
+    Ok(out)
 }
+
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day23");
     let mut position_store = [[false; ELF_POSITION_BOUND]; ELF_POSITION_BOUND];
-    let mut positions = parse_elf_positions(&contents, &mut position_store);
+    let mut positions = parse_input(&contents).expect("Could not parse input for day23");
+    for &(i, j) in positions.iter() {
+        position_store[(i + OFFSET) as usize][(j + OFFSET) as usize] = true;
+    }
     let (p1, p2) = simulate(&mut position_store, &mut positions);
     println!("Elapsed: {:?}", start.elapsed());
     println!("D23P1: {p1:?}");
@@ -23,32 +23,38 @@ enum SquareFlags {
 }
 
 impl SquareFlags {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Result<Self, String> {
         use SquareFlags::*;
         match c {
-            '>' => BlizRight,
-            '<' => BlizLeft,
-            '^' => BlizUp,
-            'v' => BlizDown,
-            '#' => Wall,
-            _ => panic!(),
+            '>' => Ok(BlizRight),
+            '<' => Ok(BlizLeft),
+            '^' => Ok(BlizUp),
+            'v' => Ok(BlizDown),
+            '#' => Ok(Wall),
+            _ => Err(format!("Unexpected square {c:?}")),
         }
     }
 }
 
-fn parse_input(in_str: &str) -> Map {
+pub(crate) fn parse_input(in_str: &str) -> Result<Map, String> {
     let mut out = vec!();
     for line in in_str.lines() {
         let mut row = vec!();
         for char in line.chars() {
             match char {
                 '.' => row.push(0),
-                _ => row.push(SquareFlags::from_char(char) as u8),
+                _ => row.push(SquareFlags::from_char(char)? as u8),
             }
         }
         out.push(row);
     }
-    out
+    if out.len() < 3 || out.iter().any(|row| row.len() != out[0].len() || row.len() < 3) {
+        return Err("The valley must be a rectangle with room inside its walls".to_string());
+    }
+    if out[START.0 as usize][START.1 as usize] != 0 {
+        return Err("The start of the valley is blocked".to_string());
+    }
+    Ok(out)
 }
 
 fn get_next_state(map: &Map) -> Map {
@@ -95,7 +101,7 @@ fn get_next_state(map: &Map) -> Map {
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day24");
-    let map = parse_input(&contents);
+    let map = parse_input(&contents).expect("Could not parse input for day24");
     let (p1, p2) = solve(map);
     println!("Elapsed: {:?}", start.elapsed());
     println!("D24P1: {p1:?}");
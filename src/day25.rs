@@ -3,17 +3,22 @@ use std::time::Instant;
 
 const FILE_PATH: &str = "inputs/day25_input.txt";
 
-fn get_value(in_str: &str) -> i64 { 
-    let mut out = 0;
+fn get_value(in_str: &str) -> Result<i64, String> {
+    let mut out: i64 = 0;
     for c in in_str.chars() {
-        out *= 5;
-        match c {
-            '-' => out -= 1,
-            '=' => out -= 2,
-            _ => out += c.to_digit(10).unwrap() as i64,
-        }
+        let digit = match c {
+            '-' => -1,
+            '=' => -2,
+            '0'..='2' => c as i64 - '0' as i64,
+            _ => return Err(format!("Invalid SNAFU digit {c:?} in {in_str:?}")),
+        };
+        out = out.checked_mul(5).and_then(|v| v.checked_add(digit)).ok_or_else(|| format!("{in_str:?} is too large"))?;
     }
-    out
+    Ok(out)
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<i64>, String> {
+    in_str.trim().lines().map(get_value).collect()
 }
 
 fn convert_to_snafu(number: i64) -> String {
@@ -30,7 +35,7 @@ fn convert_to_snafu(number: i64) -> String {
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day25");
-    let sum: i64 = contents.as_str().trim().lines().map(get_value).sum();
+    let sum: i64 = parse_input(&contents).expect("Could not parse input for day25").into_iter().sum();
     let p1 = convert_to_snafu(sum);
     println!("Elapsed: {:?}", start.elapsed());
     println!("P1: {p1:?}");
@@ -10,14 +10,27 @@ const WINDOW_SIZE: usize = 3;
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day3");
-    let parsed = contents.trim().lines();
-    let p1 = part_one(parsed.clone());
-    let p2 = part_two(parsed);
+    let parsed = parse_input(&contents).expect("Could not parse input for day3");
+    let p1 = part_one(parsed.iter().copied());
+    let p2 = part_two(parsed.iter().copied());
     println!("Elapsed: {:?}", start.elapsed());
     println!("D3P1: {p1:?}");
     println!("D3P2: {p2:?}");
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<&str>, String> {
+    in_str
+        .trim()
+        .lines()
+        .map(|sack| {
+            if sack.is_empty() || sack.len() % 2 != 0 || !sack.bytes().all(|b| b.is_ascii_alphabetic()) {
+                return Err(format!("Invalid rucksack {sack:?}"));
+            }
+            Ok(sack)
+        })
+        .collect()
+}
+
 fn find_common_byte(iterators: &[impl Iterator<Item = u8> + Clone]) -> Option<u8> {
     let first = iterators[0].clone();
     'outer: for v1 in first {
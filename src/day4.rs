@@ -5,6 +5,8 @@ use std::cmp::PartialOrd;
 
 const FILE_PATH: &str = "inputs/day4_input.txt";
 
+type Assignments = (RangeInclusive<u8>, RangeInclusive<u8>);
+
 trait ContainsOther<T=Self> {
     fn contains_other(&self, other: &T) -> bool;
 }
@@ -28,30 +30,35 @@ impl<T: PartialOrd> OverlapsOther for RangeInclusive<T> {
     }
 }
 
-
-fn main() {
-    let start = Instant::now();
-    let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day4");
-    let parsed = contents
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Assignments>, String> {
+    in_str
         .lines()
         .map(|line| {
-            let mut ranges = line.split(&['-', ',']).map(|d| d.parse().unwrap());
-            let b1: u8 = ranges.next().unwrap();
-            let e1: u8 = ranges.next().unwrap();
+            let ranges = line.split(&['-', ','])
+                .map(|d| d.parse())
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| format!("Invalid section assignment {line:?}"))?;
+            let [b1, e1, b2, e2] = ranges[..] else {
+                return Err(format!("Expected two ranges in {line:?}"));
+            };
 
-            let b2: u8 = ranges.next().unwrap();
-            let e2: u8 = ranges.next().unwrap();
+            Ok((b1..=e1, b2..=e2))
+        })
+        .collect()
+}
 
-            (b1..=e1, b2..=e2)
-        });
+fn main() {
+    let start = Instant::now();
+    let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day4");
+    let parsed = parse_input(&contents).expect("Could not parse input for day4");
 
-    let (p1, p2) = evaluate(parsed);
+    let (p1, p2) = evaluate(parsed.into_iter());
     println!("Elapsed: {:?}", start.elapsed());
     println!("D4P1: {p1:?}");
     println!("D4P2: {p2:?}");
 }
 
-fn evaluate(ranges: impl Iterator<Item = (RangeInclusive<u8>, RangeInclusive<u8>)>) -> (i32, i32) {
+fn evaluate(ranges: impl Iterator<Item = Assignments>) -> (i32, i32) {
     let mut containment_total = 0;
     let mut overlap_total = 0;
     for (r1, r2) in ranges {
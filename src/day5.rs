@@ -8,7 +8,7 @@ const FILE_PATH: &str = "inputs/day5_input.txt";
 const VALUE_OFFSET: usize = 4;
 
 #[derive(Debug)]
-struct Instruction {
+pub(crate) struct Instruction {
     from_stack: usize,
     to_stack: usize,
     count: usize,
@@ -16,9 +16,9 @@ struct Instruction {
 
 type Stacks = Vec<Vec<char>>;
 
-fn parse_stacks(raw_stacks: &str) -> Stacks {
+fn parse_stacks(raw_stacks: &str) -> Result<Stacks, String> {
     let mut stack_lines = raw_stacks.lines().rev();
-    let stack_count = stack_lines.next().unwrap()
+    let stack_count = stack_lines.next().ok_or("Missing stack numbers")?
         .split_whitespace()
         .filter(|l| !l.trim().is_empty())
         .count();
@@ -27,28 +27,41 @@ fn parse_stacks(raw_stacks: &str) -> Stacks {
     for line in stack_lines {
         for (i, c) in line.chars().skip(1).step_by(VALUE_OFFSET).enumerate() {
             if c.is_alphabetic() {
-                stacks[i].push(c);
+                stacks.get_mut(i).ok_or_else(|| format!("Crate outside of the stacks in {line:?}"))?.push(c);
             }
         }
     }
-    stacks
+    Ok(stacks)
 }
 
-fn parse_instructions(raw_instructions: &str) -> impl Iterator<Item = Instruction> + '_ + Clone{
+fn parse_instructions(raw_instructions: &str, stack_count: usize) -> Result<Vec<Instruction>, String> {
     raw_instructions
         .lines()
         .map(|l| {
-            let mut iter = l.split_whitespace().skip(1).step_by(2);
-            let count = iter.next().unwrap().parse().unwrap();
-            let from_stack: usize = iter.next().unwrap().parse().unwrap();
-            let to_stack: usize = iter.next().unwrap().parse().unwrap();
+            let words: Vec<&str> = l.split_whitespace().collect();
+            let ["move", count, "from", from_stack, "to", to_stack] = words[..] else {
+                return Err(format!("Invalid instruction {l:?}"));
+            };
+            let parse = |v: &str| v.parse::<usize>().map_err(|_| format!("Invalid instruction {l:?}"));
+            let (count, from_stack, to_stack) = (parse(count)?, parse(from_stack)?, parse(to_stack)?);
+            if !(1..=stack_count).contains(&from_stack) || !(1..=stack_count).contains(&to_stack) {
+                return Err(format!("Unknown stack in {l:?}"));
+            }
 
-            Instruction { from_stack: from_stack - 1, to_stack: to_stack - 1, count }
+            Ok(Instruction { from_stack: from_stack - 1, to_stack: to_stack - 1, count })
         })
+        .collect()
+}
+
+pub(crate) fn parse_input(in_str: &str) -> Result<(Stacks, Vec<Instruction>), String> {
+    let (raw_stacks, raw_instructions) = in_str.split_once("\n\n").ok_or("Missing instructions")?;
+    let stacks = parse_stacks(raw_stacks)?;
+    let instructions = parse_instructions(raw_instructions, stacks.len())?;
+    Ok((stacks, instructions))
 }
 
-fn part_one(instructions: impl Iterator<Item = Instruction>, stacks: &mut Stacks) {
-    for Instruction { from_stack, to_stack, count } in instructions {
+fn part_one<'a>(instructions: impl Iterator<Item = &'a Instruction>, stacks: &mut Stacks) {
+    for &Instruction { from_stack, to_stack, count } in instructions {
         for _ in 0..count {
             let v = stacks[from_stack].pop().unwrap();
             stacks[to_stack].push(v);
@@ -56,9 +69,9 @@ fn part_one(instructions: impl Iterator<Item = Instruction>, stacks: &mut Stacks
     }
 }
 
-fn part_two(instructions: impl Iterator<Item = Instruction>, stacks: &mut Stacks) {
+fn part_two<'a>(instructions: impl Iterator<Item = &'a Instruction>, stacks: &mut Stacks) {
     let mut vals: Vec<char> = vec!();
-    for Instruction { from_stack, to_stack, count } in instructions {
+    for &Instruction { from_stack, to_stack, count } in instructions {
         vals.clear();
         let from = stacks.get_mut(from_stack).unwrap();
         from.drain(from.len() - count..from.len()).collect_into(&mut vals);
@@ -72,12 +85,10 @@ fn part_two(instructions: impl Iterator<Item = Instruction>, stacks: &mut Stacks
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day5");
-    let mut contents_parts = contents.split("\n\n");
-    let mut p1_stacks = parse_stacks(contents_parts.next().unwrap());
+    let (mut p1_stacks, instructions) = parse_input(&contents).expect("Could not parse input for day5");
     let mut p2_stacks = p1_stacks.clone();
-    let instructions = parse_instructions(contents_parts.next().unwrap());
-    part_one(instructions.clone(), &mut p1_stacks);
-    part_two(instructions, &mut p2_stacks);
+    part_one(instructions.iter(), &mut p1_stacks);
+    part_two(instructions.iter(), &mut p2_stacks);
     let p1: String = p1_stacks.iter().map(|s| s[s.len() - 1]).collect();
     let p2: String = p2_stacks.iter().map(|s| s[s.len() - 1]).collect();
     println!("Elapsed: {:?}", start.elapsed());
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::{fs, collections::HashMap};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::time::Instant;
 use std::cmp::min;
 
@@ -20,14 +20,12 @@ enum Command<'a> {
 }
 
 impl<'a> Command<'a> {
-    fn from(text: &'a str) -> Self {
+    fn from(text: &'a str) -> Result<Self, String> {
         use Command::*;
-        let mut split = text.split_whitespace();
-        let first = split.next().unwrap();
-        match first {
-            "ls" => LS,
-            "cd" => CD(split.next().unwrap()),
-            _ => panic!("Failed to parse command"),
+        match text.split_whitespace().collect::<Vec<_>>()[..] {
+            ["ls"] => Ok(LS),
+            ["cd", dir] => Ok(CD(dir)),
+            _ => Err(format!("Failed to parse command {text:?}")),
         }
     }
 }
@@ -46,35 +44,35 @@ enum DirectoryContents<'a> {
 }
 
 impl<'a> DirectoryContents<'a> {
-    fn from_str_and_pwd(str: &'a str, parent: Rc<Directory<'a>>) -> Self {
-        let mut split = str.split_whitespace();
-        let first = split.next().unwrap();
-        let second = split.next().unwrap();
+    fn from_str_and_pwd(str: &'a str, parent: Rc<Directory<'a>>) -> Result<(&'a str, Self), String> {
+        let (first, second) = str.split_once(' ').ok_or_else(|| format!("Failed to parse listing {str:?}"))?;
         match first {
             "dir" => {
-                DirectoryContents::Directory::<'a>(
-                    Directory::new(second, HashMap::new(), Some(parent)).into()
-                )
+                Ok((second, DirectoryContents::Directory::<'a>(
+                    Directory::new(second, HashMap::new(), Some(Rc::downgrade(&parent))).into()
+                )))
             },
             _ => {
-                DirectoryContents::File(
-                    File { size: first.parse().unwrap(), name: second}
-                )
+                let size = first.parse().map_err(|_| format!("Invalid file size in {str:?}"))?;
+                Ok((second, DirectoryContents::File(
+                    File { size, name: second}
+                )))
             }
         }
     }
 }
 
 #[derive(Debug)]
-struct Directory<'a> {
+pub(crate) struct Directory<'a> {
     #[allow(dead_code)]
     name: &'a str,
-    parent: RefCell<Option<Rc<Directory<'a>>>>,
+    // Weak so the tree is freed once the root is dropped
+    parent: RefCell<Option<Weak<Directory<'a>>>>,
     contents: RefCell<HashMap<&'a str, DirectoryContents<'a>>>,
 }
 
 impl<'a> Directory<'a> {
-    fn new(name: &'a str, contents: HashMap<&'a str, DirectoryContents<'a>>, parent: Option<Rc<Directory<'a>>>) -> Self {
+    fn new(name: &'a str, contents: HashMap<&'a str, DirectoryContents<'a>>, parent: Option<Weak<Directory<'a>>>) -> Self {
         Directory {
             name,
             contents: RefCell::new(contents),
@@ -107,7 +105,7 @@ impl GetSize for DirectoryContents<'_> {
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day7");
-    let root = parse_input(&contents);
+    let root = parse_input(&contents).expect("Could not parse input for day7");
 
     let p1 = part_one(&root);
     let p2 = part_two(&root);
@@ -116,7 +114,7 @@ fn main() {
     println!("D7P2: {p2:?}");
 }
 
-fn parse_input(input: &str) -> Rc<Directory> {
+pub(crate) fn parse_input(input: &str) -> Result<Rc<Directory<'_>>, String> {
     let root = Rc::new(Directory::new("/", HashMap::new(), None));
     let mut next = root.clone();
     let mut lines = input.lines().peekable();
@@ -129,27 +127,29 @@ fn parse_input(input: &str) -> Rc<Directory> {
         }
         let pwd = next.clone();
         let line = line.unwrap();
-        let trailer = line.split_once(' ').unwrap().1;
+        let trailer = line.strip_prefix("$ ").ok_or_else(|| format!("Expected a command, got {line:?}"))?;
 
-        match Command::from(trailer) {
+        match Command::from(trailer)? {
             Command::LS => {
                 while let Some(peek) = lines.peek() {
                     if peek.starts_with('$') {
                         break;
                     }
                     let line = lines.next().unwrap();
-                    let dir_contents = DirectoryContents::from_str_and_pwd(line, pwd.clone());
-                    let name = line.split_whitespace().nth(1).unwrap();
+                    let (name, dir_contents) = DirectoryContents::from_str_and_pwd(line, pwd.clone())?;
                     pwd.contents.borrow_mut().insert(name, dir_contents);
                 }
             }
 
             Command::CD(x) => {
                 match x {
-                    ".." => next = pwd.parent.borrow().as_ref().unwrap().clone(),
+                    "/" => next = root.clone(),
+                    ".." => next = pwd.parent.borrow().as_ref().and_then(Weak::upgrade).ok_or("Cannot leave the root directory")?,
                     _ => {
-                        if let DirectoryContents::Directory(dir) = pwd.contents.borrow().get(x).unwrap() {
-                            next = dir.clone()
+                        match pwd.contents.borrow().get(x) {
+                            Some(DirectoryContents::Directory(dir)) => next = dir.clone(),
+                            Some(DirectoryContents::File(_)) => (),
+                            None => return Err(format!("Unknown directory {x:?}")),
                         };
                     }
                 };
@@ -157,7 +157,7 @@ fn parse_input(input: &str) -> Rc<Directory> {
         };
     }
 
-    root
+    Ok(root)
 }
 
 fn part_one(root: &Directory) -> u32 {
@@ -4,20 +4,28 @@ use std::fs;
 use std::time::Instant;
 use std::cmp::max;
 
-use crate::real_range::RealRange;
+use real_range::RealRange;
 
 const FILE_PATH: &str = "inputs/day8_input.txt";
 
 type Grid<T> = Vec<Vec<T>>;
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Grid<u32>, String> {
+    let trees: Grid<u32> = in_str
+        .lines()
+        .map(|l| {
+            l.chars().map(|c| c.to_digit(10).ok_or_else(|| format!("Invalid tree height {c:?}"))).collect()
+        }).collect::<Result<_, _>>()?;
+    if trees.is_empty() || trees.iter().any(|row| row.len() != trees.len()) {
+        return Err("The trees must form a non-empty square".to_string());
+    }
+    Ok(trees)
+}
+
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day8");
-    let parsed: Grid<u32> = contents
-        .lines()
-        .map(|l| {
-            l.chars().map(|c|c.to_digit(10).unwrap()).collect::<Vec<_>>()
-        }).collect();
+    let parsed = parse_input(&contents).expect("Could not parse input for day8");
     let (p1, p2) = count_visible(&parsed);
 
     println!("Elapsed: {:?}", start.elapsed());
@@ -10,7 +10,7 @@ const ROPE_LENGTH: usize = 10;
 struct Position { x: i32, y: i32 }
 
 #[derive(Debug)]
-enum Direction {
+pub(crate) enum Direction {
     Up(u32),
     Down(u32),
     Left(u32),
@@ -26,15 +26,16 @@ impl AddAssign for Position {
 }
 
 impl Direction {
-    fn from_str(str: &str) -> Self {
+    fn from_str(str: &str) -> Result<Self, String> {
         use Direction::*;
-        let (dir_str, count) = str.split_once(' ').unwrap();
+        let (dir_str, count) = str.split_once(' ').ok_or_else(|| format!("Received an unexpected motion {str}"))?;
+        let count = count.parse().map_err(|_| format!("Received an unexpected step count {str}"))?;
         match dir_str {
-            "U" => Up(count.parse().unwrap()),
-            "D" => Down(count.parse().unwrap()),
-            "L" => Left(count.parse().unwrap()),
-            "R" => Right(count.parse().unwrap()),
-            _ => panic!("Received an unexpected direction {str}"),
+            "U" => Ok(Up(count)),
+            "D" => Ok(Down(count)),
+            "L" => Ok(Left(count)),
+            "R" => Ok(Right(count)),
+            _ => Err(format!("Received an unexpected direction {str}")),
         }
     }
 
@@ -59,14 +60,16 @@ impl Direction {
     }
 }
 
+pub(crate) fn parse_input(in_str: &str) -> Result<Vec<Direction>, String> {
+    in_str.lines().map(Direction::from_str).collect()
+}
+
 fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string(FILE_PATH).expect("Could not read input for day9");
-    let parsed = contents
-        .lines()
-        .map(Direction::from_str);
-        
-    let (p1, p2) = solve(parsed);
+    let parsed = parse_input(&contents).expect("Could not parse input for day9");
+
+    let (p1, p2) = solve(parsed.into_iter());
 
     println!("Elapsed: {:?}", start.elapsed());
     println!("D9P1: {p1:?}");
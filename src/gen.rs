@@ -0,0 +1,913 @@
+use std::cmp::max;
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use num::integer::lcm;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rustc_hash::FxHashSet;
+
+type Generator = fn(&mut ChaCha8Rng, usize) -> String;
+
+// (day, default size, generator). What "size" scales is noted on each generator.
+const GENERATORS: [(u8, usize, Generator); 25] = [
+    (1, 250, day1),
+    (2, 2500, day2),
+    (3, 300, day3),
+    (4, 1000, day4),
+    (5, 500, day5),
+    (6, 4000, day6),
+    (7, 200, day7),
+    (8, 99, day8),
+    (9, 2000, day9),
+    (10, 0, day10),
+    (11, 8, day11),
+    (12, 40, day12),
+    (13, 150, day13),
+    (14, 150, day14),
+    (15, 20, day15),
+    (16, 15, day16),
+    (17, 10_000, day17),
+    (18, 2500, day18),
+    (19, 30, day19),
+    (20, 5000, day20),
+    (21, 2000, day21),
+    (22, 50, day22),
+    (23, 70, day23),
+    (24, 25, day24),
+    (25, 120, day25),
+];
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Builds a random but well-formed input for `day`. The same seed and size always produce the
+/// same input; `None` means there is no generator for that day.
+pub fn generate(day: u8, seed: u64, size: Option<usize>) -> Option<String> {
+    let &(_, default_size, generator) = GENERATORS.iter().find(|(d, _, _)| *d == day)?;
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    Some(generator(&mut rng, size.unwrap_or(default_size)))
+}
+
+fn random_name(rng: &mut ChaCha8Rng, alphabet: &[u8], length: usize) -> String {
+    (0..length).map(|_| *alphabet.choose(rng).unwrap() as char).collect()
+}
+
+// size: number of elves
+fn day1(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let elves: Vec<String> = (0..max(size, 3))
+        .map(|_| {
+            let items: Vec<String> = (0..rng.gen_range(1..=15))
+                .map(|_| rng.gen_range(1000..=60_000).to_string())
+                .collect();
+            items.join("\n")
+        })
+        .collect();
+    elves.join("\n\n")
+}
+
+// size: number of rounds
+fn day2(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| format!("{} {}", ["A", "B", "C"].choose(rng).unwrap(), ["X", "Y", "Z"].choose(rng).unwrap()))
+        .collect();
+    lines.join("\n")
+}
+
+// size: number of rucksacks, rounded up to a whole group of three
+fn day3(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let mut lines = vec!();
+    for _ in 0..max(size.div_ceil(3), 1) {
+        let mut letters = LETTERS.to_vec();
+        letters.shuffle(rng);
+        // Each elf in the group draws from its own pool so the badge is the only shared item
+        let badge = letters.pop().unwrap();
+        for pool in letters.chunks(letters.len() / 3).take(3) {
+            let (shared, rest) = pool.split_first().unwrap();
+            let (first_pool, second_pool) = rest.split_at(rest.len() / 2);
+            let compartment_size = rng.gen_range(4..=16);
+
+            let mut first = vec!(*shared, badge);
+            let mut second = vec!(*shared);
+            first.extend((2..compartment_size).map(|_| *first_pool.choose(rng).unwrap()));
+            second.extend((1..compartment_size).map(|_| *second_pool.choose(rng).unwrap()));
+            first.shuffle(rng);
+            second.shuffle(rng);
+            first.extend(second);
+            lines.push(String::from_utf8(first).unwrap());
+        }
+    }
+    lines.join("\n")
+}
+
+// size: number of assignment pairs
+fn day4(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let range = |rng: &mut ChaCha8Rng| {
+        let start = rng.gen_range(1..=99);
+        (start, rng.gen_range(start..=99))
+    };
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| {
+            let ((b1, e1), (b2, e2)) = (range(rng), range(rng));
+            format!("{b1}-{e1},{b2}-{e2}")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// size: number of moves
+fn day5(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let stack_count = rng.gen_range(3..=9);
+    let mut stacks: Vec<Vec<u8>> = (0..stack_count)
+        .map(|_| (0..rng.gen_range(1..=8)).map(|_| *b"ABCDEFGHIJKLMNOPQRSTUVWXYZ".choose(rng).unwrap()).collect())
+        .collect();
+    // Moves need a stack with a crate to spare. Since no stack is ever emptied there's always one
+    // from then on, as there are more crates than stacks
+    if stacks.iter().all(|s| s.len() < 2) {
+        let spare = *b"ABCDEFGHIJKLMNOPQRSTUVWXYZ".choose(rng).unwrap();
+        stacks.choose_mut(rng).unwrap().push(spare);
+    }
+
+    let mut out = String::new();
+    let tallest = stacks.iter().map(Vec::len).max().unwrap();
+    for height in (0..tallest).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|s| s.get(height).map_or("   ".to_string(), |&c| format!("[{}]", c as char)))
+            .collect();
+        writeln!(out, "{}", row.join(" ")).unwrap();
+    }
+    let labels: Vec<String> = (1..=stack_count).map(|i| format!(" {i} ")).collect();
+    writeln!(out, "{}\n", labels.join(" ")).unwrap();
+
+    // Never empty a stack so every stack still has a crate on top at the end
+    let mut moves = vec!();
+    while moves.len() < max(size, 1) {
+        let from = rng.gen_range(0..stack_count);
+        let to = rng.gen_range(0..stack_count);
+        if from == to || stacks[from].len() < 2 {
+            continue;
+        }
+        let count = rng.gen_range(1..stacks[from].len());
+        let split = stacks[from].len() - count;
+        let moved = stacks[from].split_off(split);
+        stacks[to].extend(moved);
+        moves.push(format!("move {count} from {} to {}", from + 1, to + 1));
+    }
+    out + &moves.join("\n")
+}
+
+// size: length of the noise before the first 14 character marker
+fn day6(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let mut letters = LOWERCASE.to_vec();
+    letters.shuffle(rng);
+    // Too few distinct letters in the noise to ever form a start-of-message marker
+    let noise_alphabet = &letters[..rng.gen_range(4..=13)];
+    let mut out: Vec<u8> = (0..size).map(|_| *noise_alphabet.choose(rng).unwrap()).collect();
+    letters.shuffle(rng);
+    out.extend(&letters[..14]);
+    out.extend((0..rng.gen_range(0..=size)).map(|_| *LOWERCASE.choose(rng).unwrap()));
+    String::from_utf8(out).unwrap()
+}
+
+// size: number of directories
+fn day7(rng: &mut ChaCha8Rng, size: usize) -> String {
+    struct Dir {
+        name: String,
+        files: Vec<(String, u64)>,
+        children: Vec<usize>,
+    }
+
+    let mut dirs = vec!(Dir { name: "/".to_string(), files: vec!(), children: vec!() });
+    let mut taken: Vec<FxHashSet<String>> = vec!(FxHashSet::default());
+    let unique_name = |rng: &mut ChaCha8Rng, taken: &mut FxHashSet<String>, extension: bool| loop {
+        let length = rng.gen_range(1..=8);
+        let mut name = random_name(rng, LOWERCASE, length);
+        if extension && rng.gen_bool(0.5) {
+            name = format!("{name}.{}", random_name(rng, LOWERCASE, 3));
+        }
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    for _ in 1..max(size, 1) {
+        let parent = rng.gen_range(0..dirs.len());
+        let name = unique_name(rng, &mut taken[parent], false);
+        let index = dirs.len();
+        dirs[parent].children.push(index);
+        dirs.push(Dir { name, files: vec!(), children: vec!() });
+        taken.push(FxHashSet::default());
+    }
+
+    // Weight the files then scale them so the disk is between 45M and 65M full, which makes
+    // part two need to free up something
+    let mut total_weight = 0;
+    for (i, dir) in dirs.iter_mut().enumerate() {
+        for _ in 0..rng.gen_range(if i == 0 { 1 } else { 0 }..=5) {
+            let name = unique_name(rng, &mut taken[i], true);
+            let weight = rng.gen_range(1..=1000);
+            total_weight += weight;
+            dir.files.push((name, weight));
+        }
+    }
+    let disk_usage = rng.gen_range(45_000_000..=65_000_000);
+    for (_, size) in dirs.iter_mut().flat_map(|d| d.files.iter_mut()) {
+        *size = max(1, *size * disk_usage / total_weight);
+    }
+
+    fn transcript(dirs: &[Dir], index: usize, out: &mut Vec<String>) {
+        let dir = &dirs[index];
+        out.push("$ ls".to_string());
+        for &child in &dir.children {
+            out.push(format!("dir {}", dirs[child].name));
+        }
+        for (name, size) in &dir.files {
+            out.push(format!("{size} {name}"));
+        }
+        for &child in &dir.children {
+            out.push(format!("$ cd {}", dirs[child].name));
+            transcript(dirs, child, out);
+            out.push("$ cd ..".to_string());
+        }
+    }
+
+    let mut out = vec!("$ cd /".to_string());
+    transcript(&dirs, 0, &mut out);
+    out.join("\n")
+}
+
+// size: width and height of the forest
+fn day8(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| (0..max(size, 1)).map(|_| char::from_digit(rng.gen_range(0..10), 10).unwrap()).collect())
+        .collect();
+    lines.join("\n")
+}
+
+// size: number of head motions
+fn day9(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| format!("{} {}", ["U", "D", "L", "R"].choose(rng).unwrap(), rng.gen_range(1..=20)))
+        .collect();
+    lines.join("\n")
+}
+
+// size: unused, the CRT always runs for 240 cycles
+fn day10(rng: &mut ChaCha8Rng, _size: usize) -> String {
+    let mut lines = vec!();
+    let (mut cycles, mut x) = (0, 1);
+    while cycles < 240 {
+        if cycles + 2 <= 240 && rng.gen_bool(0.6) {
+            // Keep the sprite roughly on screen
+            let v = loop {
+                let v: i32 = rng.gen_range(-15..=15);
+                if v != 0 && (-1..=40).contains(&(x + v)) {
+                    break v;
+                }
+            };
+            x += v;
+            cycles += 2;
+            lines.push(format!("addx {v}"));
+        } else {
+            cycles += 1;
+            lines.push("noop".to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+// size: number of monkeys, at most 8 so the product of the divisors stays small
+fn day11(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let count = size.clamp(2, 8);
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    divisors.shuffle(rng);
+    let squarer = rng.gen_range(0..count);
+
+    let monkeys: Vec<String> = (0..count)
+        .map(|i| {
+            let items: Vec<String> = (0..rng.gen_range(1..=6)).map(|_| rng.gen_range(50..100).to_string()).collect();
+            let operation = if i == squarer {
+                "old * old".to_string()
+            } else if rng.gen_bool(0.5) {
+                format!("old * {}", rng.gen_range(2..=19))
+            } else {
+                format!("old + {}", rng.gen_range(1..=8))
+            };
+            let mut targets: Vec<usize> = (0..count).filter(|&j| j != i).collect();
+            targets.shuffle(rng);
+            let (true_target, false_target) = if count == 2 { (targets[0], targets[0]) } else { (targets[0], targets[1]) };
+
+            format!(
+                "Monkey {i}:\n  Starting items: {}\n  Operation: new = {operation}\n  Test: divisible by {}\n    If true: throw to monkey {true_target}\n    If false: throw to monkey {false_target}",
+                items.join(", "),
+                divisors[i],
+            )
+        })
+        .collect();
+    monkeys.join("\n\n")
+}
+
+// size: number of rows, the map is at least 40 columns wide so the foothills reach 'a'
+fn day12(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let (height, width) = (max(size, 5), max(size * 4, 40));
+    loop {
+        let end = (rng.gen_range(0..height), rng.gen_range(width * 3 / 4..width));
+        let start = (rng.gen_range(0..height), 0);
+        // How far from the summit the slope reaches the bottom, never steeper than one per step
+        let reach = rng.gen_range(25..=width * 3 / 4);
+        let mut grid: Vec<Vec<u8>> = (0..height)
+            .map(|r| {
+                (0..width)
+                    .map(|c| {
+                        let distance = r.abs_diff(end.0) + c.abs_diff(end.1);
+                        let mut height = 25 - (distance.min(reach) * 25 / reach) as u8;
+                        // Roughen the slope, the search below throws away maps that lose their route
+                        if height > 0 && rng.gen_bool(0.3) {
+                            height -= rng.gen_range(1..=height.min(3));
+                        }
+                        b'a' + height
+                    })
+                    .collect()
+            })
+            .collect();
+        grid[start.0][start.1] = b'a';
+        grid[end.0][end.1] = b'z';
+
+        if is_climbable(&grid, start, end) {
+            grid[start.0][start.1] = b'S';
+            grid[end.0][end.1] = b'E';
+            let lines: Vec<String> = grid.into_iter().map(|r| String::from_utf8(r).unwrap()).collect();
+            return lines.join("\n");
+        }
+    }
+}
+
+fn is_climbable(grid: &[Vec<u8>], start: (usize, usize), end: (usize, usize)) -> bool {
+    let mut visited = FxHashSet::default();
+    let mut queue = VecDeque::from([start]);
+    visited.insert(start);
+    while let Some((r, c)) = queue.pop_front() {
+        if (r, c) == end {
+            return true;
+        }
+        let neighbours = [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)];
+        for (r1, c1) in neighbours {
+            if r1 < grid.len() && c1 < grid[0].len() && grid[r1][c1] <= grid[r][c] + 1 && visited.insert((r1, c1)) {
+                queue.push_back((r1, c1));
+            }
+        }
+    }
+    false
+}
+
+// size: number of packet pairs
+fn day13(rng: &mut ChaCha8Rng, size: usize) -> String {
+    fn packet(rng: &mut ChaCha8Rng, depth: u32) -> String {
+        let items: Vec<String> = (0..rng.gen_range(0..=5))
+            .map(|_| {
+                if depth < 4 && rng.gen_bool(0.3) {
+                    packet(rng, depth + 1)
+                } else {
+                    rng.gen_range(0..=10).to_string()
+                }
+            })
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+
+    let pairs: Vec<String> = (0..max(size, 1))
+        .map(|_| format!("{}\n{}", packet(rng, 0), packet(rng, 0)))
+        .collect();
+    pairs.join("\n\n")
+}
+
+// size: number of rock paths, the cave also gets deeper and wider as it grows
+fn day14(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let depth = 10 + size as i32;
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| {
+            let (mut x, mut y) = (rng.gen_range(500 - depth..=500 + depth), rng.gen_range(2..=depth));
+            let mut points = vec!(format!("{x},{y}"));
+            let mut horizontal = rng.gen_bool(0.5);
+            for _ in 0..rng.gen_range(1..=5) {
+                let length = rng.gen_range(1..=8) * if rng.gen_bool(0.5) { 1 } else { -1 };
+                if horizontal {
+                    x += length;
+                } else {
+                    y = (y + length).clamp(2, depth);
+                }
+                horizontal = !horizontal;
+                points.push(format!("{x},{y}"));
+            }
+            points.join(" -> ")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// size: scales the number of sensors, about one and a half per unit
+fn day15(rng: &mut ChaCha8Rng, size: usize) -> String {
+    day15_with_bound(rng, size, 4_000_000)
+}
+
+// Works in rotated coordinates (u = x + y, v = x - y) where every sensor range is a square and
+// distance is the larger of |du| and |dv|. The plane either side of the distress beacon's u is
+// cut into strips of squares, and the distress beacon's own column gets a stack of squares
+// with a one point gap. Squares only overlap along shared edges, so each beacon can sit on
+// its sensor's edge without being inside, or tied with, any other sensor's range.
+fn day15_with_bound(rng: &mut ChaCha8Rng, size: usize, bound: i64) -> String {
+    let bound = max(bound, 8);
+    let (x, y) = (rng.gen_range(0..=bound), rng.gen_range(0..=bound));
+    let (du, dv) = (x + y, x - y);
+    let (v_min, v_max) = (-bound, bound);
+    let budget = max(size, 4) as i64;
+
+    let mut sensors: Vec<((i64, i64), (i64, i64))> = vec!();
+    let mut emit = |centre: (i64, i64), beacon: (i64, i64)| sensors.push((centre, beacon));
+
+    // The distress column: squares of even radius stacked outwards from two above and two below
+    // the distress beacon. Neighbouring squares share a corner on the column, and every other
+    // shared corner is a beacon for both squares so no sensor sees two beacons at once.
+    // Sized so roughly a quarter of the sensors end up in the distress column
+    let column_radius = max(2, bound * 4 / budget) & !1;
+    let strip_radius = max(2, (bound as f64 / (0.75 * budget as f64).sqrt()) as i64);
+    for direction in [1, -1] {
+        let mut edge = dv + 2 * direction;
+        let mut index = 0;
+        while (edge - dv) * direction <= 2 * bound {
+            let far_edge = edge + 2 * column_radius * direction;
+            let beacon_v = if index % 2 == 0 { edge } else { far_edge };
+            emit((du, edge + column_radius * direction), (du, beacon_v));
+            edge = far_edge;
+            index += 1;
+        }
+    }
+
+    // The strips either side, the nearest ones wide enough to clear the column squares
+    for direction in [-1, 1] {
+        let limit = if direction == -1 { du } else { 2 * bound - du };
+        let mut edge = du + direction;
+        let mut first = true;
+        while first || (edge - du) * direction <= limit {
+            let min_radius = if first { column_radius + 1 } else { 2 };
+            let radius = rng.gen_range(max(min_radius, strip_radius * 2 / 3)..=max(min_radius, strip_radius * 4 / 3));
+            let centre_u = edge + radius * direction;
+            // Beacons go on the edge furthest from the distress column
+            let beacon_u = centre_u + radius * direction;
+            let mut v = v_min - rng.gen_range(0..=radius);
+            loop {
+                let centre_v = if (v + radius - centre_u).rem_euclid(2) == 0 { v + radius } else { v + radius + 1 };
+                let offset = rng.gen_range(1 - radius..radius);
+                let beacon_v = if (centre_v + offset - beacon_u).rem_euclid(2) == 0 { centre_v + offset } else { centre_v + offset + if offset < 0 { 1 } else { -1 } };
+                emit((centre_u, centre_v), (beacon_u, beacon_v));
+                if centre_v + radius >= v_max {
+                    break;
+                }
+                v = centre_v + radius;
+            }
+            edge += (2 * radius + 1) * direction;
+            first = false;
+        }
+    }
+
+    let to_xy = |(u, v): (i64, i64)| ((u + v) / 2, (u - v) / 2);
+    sensors.shuffle(rng);
+    let lines: Vec<String> = sensors
+        .into_iter()
+        .map(|(sensor, beacon)| {
+            let ((sx, sy), (bx, by)) = (to_xy(sensor), to_xy(beacon));
+            format!("Sensor at x={sx}, y={sy}: closest beacon is at x={bx}, y={by}")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// size: number of valves with a non-zero flow rate, at most 100 so there are enough names
+fn day16(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let size = size.min(100);
+    let mut names = FxHashSet::default();
+    let mut valves = vec!();
+    let mut flow_rates = vec!();
+    let mut add_valve = |rng: &mut ChaCha8Rng, flow_rate: u32| loop {
+        let name = if valves.is_empty() { "AA".to_string() } else { random_name(rng, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ", 2) };
+        if names.insert(name.clone()) {
+            valves.push(name);
+            flow_rates.push(flow_rate);
+            return valves.len() - 1;
+        }
+    };
+
+    // Like the real inputs: the useful valves and a few empty junctions, joined up by
+    // corridors of empty valves with exactly two tunnels each
+    let start = add_valve(rng, 0);
+    let mut rooms = vec!(start);
+    for _ in 0..size {
+        let flow_rate = rng.gen_range(1..=25);
+        rooms.push(add_valve(rng, flow_rate));
+    }
+    for _ in 0..size / 2 {
+        rooms.push(add_valve(rng, 0));
+    }
+    rooms[1..].shuffle(rng);
+
+    let mut corridors: Vec<(usize, usize)> = (1..rooms.len()).map(|i| (rooms[rng.gen_range(0..i)], rooms[i])).collect();
+    for _ in 0..rooms.len() / 4 {
+        let (a, b) = (*rooms.choose(rng).unwrap(), *rooms.choose(rng).unwrap());
+        if a != b && !corridors.contains(&(a, b)) && !corridors.contains(&(b, a)) {
+            corridors.push((a, b));
+        }
+    }
+    let mut links = vec!();
+    for (a, b) in corridors {
+        let mut previous = a;
+        for _ in 0..rng.gen_range(0..=3) {
+            let next = add_valve(rng, 0);
+            links.push((previous, next));
+            previous = next;
+        }
+        links.push((previous, b));
+    }
+    let mut tunnels: Vec<Vec<usize>> = vec!(vec!(); valves.len());
+    for (a, b) in links {
+        tunnels[a].push(b);
+        tunnels[b].push(a);
+    }
+
+    let mut order: Vec<usize> = (0..valves.len()).collect();
+    order.shuffle(rng);
+    let lines: Vec<String> = order
+        .into_iter()
+        .map(|i| {
+            let targets: Vec<&str> = tunnels[i].iter().map(|&t| valves[t].as_str()).collect();
+            let tunnel_text = if targets.len() == 1 { "tunnel leads to valve" } else { "tunnels lead to valves" };
+            format!("Valve {} has flow rate={}; {tunnel_text} {}", valves[i], flow_rates[i], targets.join(", "))
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// size: number of jets in the pattern
+fn day17(rng: &mut ChaCha8Rng, size: usize) -> String {
+    (0..max(size, 1)).map(|_| if rng.gen_bool(0.5) { '<' } else { '>' }).collect()
+}
+
+// size: number of cubes in the droplet
+fn day18(rng: &mut ChaCha8Rng, size: usize) -> String {
+    const OFFSETS: [(i32, i32, i32); 6] = [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)];
+    let side = max(4, (size as f64).cbrt().ceil() as i32 + 2);
+    let centre = (side / 2, side / 2, side / 2);
+    let mut cubes = vec!(centre);
+    let mut placed = FxHashSet::default();
+    placed.insert(centre);
+
+    // Grow a blob from the centre so it picks up some enclosed pockets along the way
+    while cubes.len() < max(size, 1) {
+        let (x, y, z) = *cubes.choose(rng).unwrap();
+        let (dx, dy, dz) = *OFFSETS.choose(rng).unwrap();
+        let next = (x + dx, y + dy, z + dz);
+        let in_bounds = [next.0, next.1, next.2].iter().all(|v| (0..side * 2).contains(v));
+        if in_bounds && placed.insert(next) {
+            cubes.push(next);
+        }
+    }
+
+    cubes.shuffle(rng);
+    let lines: Vec<String> = cubes.into_iter().map(|(x, y, z)| format!("{x},{y},{z}")).collect();
+    lines.join("\n")
+}
+
+// size: number of blueprints, at least three for part two
+fn day19(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let lines: Vec<String> = (1..=max(size, 3))
+        .map(|id| {
+            format!(
+                "Blueprint {id}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+                rng.gen_range(2..=4),
+                rng.gen_range(2..=4),
+                rng.gen_range(2..=4),
+                rng.gen_range(5..=20),
+                rng.gen_range(2..=4),
+                rng.gen_range(5..=20),
+            )
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// size: length of the encrypted file
+fn day20(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let mut values: Vec<i64> = (1..max(size, 4))
+        .map(|_| loop {
+            let v = rng.gen_range(-10_000..=10_000);
+            if v != 0 {
+                break v;
+            }
+        })
+        .collect();
+    values.push(0);
+    values.shuffle(rng);
+    let lines: Vec<String> = values.iter().map(i64::to_string).collect();
+    lines.join("\n")
+}
+
+// size: roughly the number of monkeys
+fn day21(rng: &mut ChaCha8Rng, size: usize) -> String {
+    // Values are chosen top down so every division is exact. The humn side of root is built
+    // around the answer to part two, and the number humn actually yells differs from it by a
+    // multiple of every divisor on its path so part one divides exactly too.
+    struct Builder<'a> {
+        rng: &'a mut ChaCha8Rng,
+        lines: Vec<String>,
+        names: FxHashSet<String>,
+    }
+
+    impl Builder<'_> {
+        fn name(&mut self) -> String {
+            loop {
+                let name = random_name(self.rng, LOWERCASE, 4);
+                if name != "root" && name != "humn" && self.names.insert(name.clone()) {
+                    return name;
+                }
+            }
+        }
+
+        fn subtree(&mut self, value: i64, budget: usize) -> String {
+            let name = self.name();
+            if budget < 3 {
+                self.lines.push(format!("{name}: {value}"));
+                return name;
+            }
+            let (lhs, op, rhs) = self.split(value);
+            let left_budget = self.rng.gen_range(1..budget - 1);
+            let (lhs, rhs) = (self.subtree(lhs, left_budget), self.subtree(rhs, budget - 1 - left_budget));
+            self.lines.push(format!("{name}: {lhs} {op} {rhs}"));
+            name
+        }
+
+        fn split(&mut self, value: i64) -> (i64, char, i64) {
+            loop {
+                match self.rng.gen_range(0..4) {
+                    0 if value >= 2 => {
+                        let lhs = self.rng.gen_range(1..value);
+                        return (lhs, '+', value - lhs);
+                    },
+                    1 => {
+                        let rhs = self.rng.gen_range(1..=100);
+                        return (value + rhs, '-', rhs);
+                    },
+                    2 => {
+                        let divisors: Vec<i64> = (2..=12).filter(|d| value % d == 0).collect();
+                        if let Some(&d) = divisors.choose(self.rng) {
+                            return (value / d, '*', d);
+                        }
+                    },
+                    3 if value < 1_000_000_000 => {
+                        let rhs = self.rng.gen_range(2..=10);
+                        return (value * rhs, '/', rhs);
+                    },
+                    _ => continue,
+                }
+            }
+        }
+    }
+
+    let mut builder = Builder { rng, lines: vec!(), names: FxHashSet::default() };
+    let target = builder.rng.gen_range(1_000..=100_000);
+    let budget = max(size, 8);
+    let other_side = builder.subtree(target, budget / 2);
+
+    // (name, operator, sibling, humn is on the left)
+    let mut path = vec!();
+    let mut value = target;
+    let (mut divisor_product, mut multiplier_product): (i64, i64) = (1, 1);
+    while path.len() < max(2, (budget as f64).log2() as usize) {
+        let n = builder.rng.gen_range(2..=20);
+        // (operator, value below, sibling value, humn is on the left)
+        let (op, child, sibling_value, on_left) = match builder.rng.gen_range(0..5) {
+            0 if value > n => ('+', value - n, n, builder.rng.gen_bool(0.5)),
+            1 => ('-', value + n, n, true),
+            2 => ('-', n, value + n, false),
+            3 if value % n == 0 && multiplier_product < 1_000_000 => {
+                multiplier_product *= n;
+                ('*', value / n, n, builder.rng.gen_bool(0.5))
+            },
+            // Never put humn on the right of a division, part two can't invert that
+            4 if divisor_product < 10_000 && value < 1_000_000_000 => {
+                divisor_product *= n;
+                ('/', value * n, n, true)
+            },
+            _ => continue,
+        };
+        let name = builder.name();
+        let sibling_budget = builder.rng.gen_range(1..=max(1, budget / 8));
+        let sibling = builder.subtree(sibling_value, sibling_budget);
+        path.push((name, op, sibling, on_left));
+        value = child;
+    }
+
+    let yelled = value + divisor_product * builder.rng.gen_range(1..=5);
+    builder.lines.push(format!("humn: {yelled}"));
+    let mut below = "humn".to_string();
+    for (name, op, sibling, on_left) in path.into_iter().rev() {
+        let line = if on_left { format!("{name}: {below} {op} {sibling}") } else { format!("{name}: {sibling} {op} {below}") };
+        builder.lines.push(line);
+        below = name;
+    }
+    let root_op = ['+', '-'].choose(builder.rng).unwrap();
+    let root = if builder.rng.gen_bool(0.5) { format!("root: {below} {root_op} {other_side}") } else { format!("root: {other_side} {root_op} {below}") };
+    builder.lines.push(root);
+
+    let mut lines = builder.lines;
+    lines.shuffle(rng);
+    lines.join("\n")
+}
+
+// size: side length of each cube face, laid out in the same net as the real inputs
+fn day22(rng: &mut ChaCha8Rng, size: usize) -> String {
+    const FACES: [(usize, usize); 6] = [(0, 1), (0, 2), (1, 1), (2, 0), (2, 1), (3, 0)];
+    let face_size = max(size, 2);
+    let mut map = vec!(vec!(b' '; face_size * 3); face_size * 4);
+    for (face_row, face_col) in FACES {
+        for row in map.iter_mut().skip(face_row * face_size).take(face_size) {
+            for square in row.iter_mut().skip(face_col * face_size).take(face_size) {
+                *square = if rng.gen_bool(0.1) { b'#' } else { b'.' };
+            }
+        }
+    }
+    // The walk starts at the top left of the first face
+    map[0][face_size] = b'.';
+
+    let mut out = String::new();
+    for row in map {
+        writeln!(out, "{}", String::from_utf8(row).unwrap().trim_end()).unwrap();
+    }
+    out.push('\n');
+    for _ in 0..face_size * 4 {
+        write!(out, "{}{}", rng.gen_range(1..=face_size), if rng.gen_bool(0.5) { 'L' } else { 'R' }).unwrap();
+    }
+    write!(out, "{}", rng.gen_range(1..=face_size)).unwrap();
+    out
+}
+
+// size: width and height of the starting grove
+fn day23(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let density = rng.gen_range(0.3..0.5);
+    let lines: Vec<String> = (0..max(size, 1))
+        .map(|_| (0..max(size, 1)).map(|_| if rng.gen_bool(density) { '#' } else { '.' }).collect())
+        .collect();
+    lines.join("\n")
+}
+
+// size: height of the valley, which is five times as wide
+fn day24(rng: &mut ChaCha8Rng, size: usize) -> String {
+    let (height, width) = (max(size, 2), max(size, 2) * 5);
+    loop {
+        let mut valley = vec!(vec!(b'#'; width + 2); height + 2);
+        for row in valley.iter_mut().skip(1).take(height) {
+            for (c, square) in row.iter_mut().enumerate().skip(1).take(width) {
+                // No vertical blizzards in the entrance and exit columns
+                let choices: &[u8] = if c == 1 || c == width { b"<>" } else { b"<>^v" };
+                *square = if rng.gen_bool(0.35) { *choices.choose(rng).unwrap() } else { b'.' };
+            }
+        }
+        valley[0][1] = b'.';
+        valley[height + 1][width] = b'.';
+
+        if is_crossable(&valley) {
+            let lines: Vec<String> = valley.into_iter().map(|r| String::from_utf8(r).unwrap()).collect();
+            return lines.join("\n");
+        }
+    }
+}
+
+// Checks the there, back and there again trips can all be made
+fn is_crossable(valley: &[Vec<u8>]) -> bool {
+    let (height, width) = (valley.len() as i64 - 2, valley[0].len() as i64 - 2);
+    let period = lcm(height, width);
+    let blocked = |r: i64, c: i64, t: i64| {
+        if r < 0 || r > height + 1 || c < 0 || c > width + 1 || valley[r as usize][c as usize] == b'#' {
+            return true;
+        }
+        if r == 0 || r == height + 1 {
+            return false;
+        }
+        let wrap = |v: i64, n: i64| (v - 1).rem_euclid(n) as usize + 1;
+        valley[r as usize][wrap(c - t, width)] == b'>'
+            || valley[r as usize][wrap(c + t, width)] == b'<'
+            || valley[wrap(r - t, height)][c as usize] == b'v'
+            || valley[wrap(r + t, height)][c as usize] == b'^'
+    };
+
+    let (start, goal) = ((0, 1), (height + 1, width));
+    let mut time = 0;
+    for (from, to) in [(start, goal), (goal, start), (start, goal)] {
+        let mut visited = FxHashSet::default();
+        let mut queue = VecDeque::from([(from, time)]);
+        let mut arrival = None;
+        while let Some(((r, c), t)) = queue.pop_front() {
+            if (r, c) == to {
+                arrival = Some(t);
+                break;
+            }
+            for (dr, dc) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let next = ((r + dr, c + dc), t + 1);
+                if !blocked(next.0 .0, next.0 .1, next.1) && visited.insert((next.0, next.1 % period)) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        match arrival {
+            Some(t) => time = t,
+            None => return false,
+        }
+    }
+    true
+}
+
+// size: number of fuel requirements
+fn day25(rng: &mut ChaCha8Rng, size: usize) -> String {
+    fn to_snafu(mut value: i64) -> String {
+        let mut digits = vec!();
+        while value != 0 {
+            let (digit, carry) = match value % 5 {
+                3 => ('=', 1),
+                4 => ('-', 1),
+                d => (char::from_digit(d as u32, 10).unwrap(), 0),
+            };
+            digits.push(digit);
+            value = value / 5 + carry;
+        }
+        digits.iter().rev().collect()
+    }
+
+    let lines: Vec<String> = (0..max(size, 1)).map(|_| to_snafu(rng.gen_range(1..=1_000_000_000_000))).collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=25 {
+            assert_eq!(generate(day, 7, Some(10)), generate(day, 7, Some(10)));
+        }
+        assert_ne!(generate(13, 1, None), generate(13, 2, None));
+        assert_eq!(generate(26, 1, None), None);
+    }
+
+    #[test]
+    fn generators_finish() {
+        // Run in another thread so a generator stuck in a loop fails the test instead of hanging it
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for day in 1..=25 {
+                for seed in 0..200 {
+                    sender.send((day, seed, generate(day, seed, Some(10)).is_some())).unwrap();
+                }
+            }
+        });
+        let (mut day, mut seed) = (1, 0);
+        for _ in 0..25 * 200 {
+            let generated;
+            (day, seed, generated) = receiver.recv_timeout(Duration::from_secs(10))
+                .unwrap_or_else(|_| panic!("generator stuck after day {day} seed {seed}"));
+            assert!(generated);
+        }
+        // The crates in this one start out a single crate to a stack
+        assert!(generate(5, 2782, None).is_some());
+    }
+
+    #[test]
+    fn day15_leaves_exactly_one_gap() {
+        for seed in 0..20 {
+            let bound = 30;
+            let input = day15_with_bound(&mut ChaCha8Rng::seed_from_u64(seed), 6, bound);
+            let sensors: Vec<Vec<i64>> = input
+                .lines()
+                .map(|l| l.split(|c: char| c != '-' && !c.is_ascii_digit()).filter_map(|n| n.parse().ok()).collect())
+                .collect();
+            let beacons: FxHashSet<(i64, i64)> = sensors.iter().map(|s| (s[2], s[3])).collect();
+            let distance = |s: &[i64], (x, y): (i64, i64)| (s[0] - x).abs() + (s[1] - y).abs();
+
+            for sensor in &sensors {
+                let radius = distance(sensor, (sensor[2], sensor[3]));
+                let at_or_inside = beacons.iter().filter(|&&b| distance(sensor, b) <= radius).count();
+                assert_eq!(at_or_inside, 1, "seed {seed}: sensor {sensor:?} isn't closest to its own beacon");
+            }
+
+            let gaps = (0..=bound)
+                .flat_map(|x| (0..=bound).map(move |y| (x, y)))
+                .filter(|&p| sensors.iter().all(|s| distance(s, p) > distance(s, (s[2], s[3]))))
+                .count();
+            assert_eq!(gaps, 1, "seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Panic,
+    Timeout,
+}
+
+struct Run {
+    input: PathBuf,
+    status: Status,
+    elapsed: Duration,
+    answers: Vec<String>,
+}
+
+/// Runs every day binary over every `dayN_*.txt` under `dir` and prints a matrix of the results.
+/// Returns whether every run finished without panicking or timing out.
+pub fn run(dir: &Path, timeout: Duration) -> io::Result<bool> {
+    let mut inputs = vec!();
+    find_inputs(dir, &mut inputs)?;
+    inputs.sort();
+    if inputs.is_empty() {
+        println!("No dayN_*.txt inputs found under {}", dir.display());
+        return Ok(true);
+    }
+
+    // Every solver reads inputs/dayN_input.txt from its working directory, so each run gets a
+    // scratch directory with the input copied in there
+    let scratch = env::temp_dir().join(format!("aoc-matrix-{}", process::id()));
+    fs::create_dir_all(scratch.join("inputs"))?;
+
+    let mut all_ok = true;
+    let mut day_start = 0;
+    while day_start < inputs.len() {
+        let day = inputs[day_start].0;
+        let day_end = day_start + inputs[day_start..].iter().take_while(|(d, _)| *d == day).count();
+        let binary = solver_path(day)?;
+        if !binary.exists() {
+            println!("day{day}: no binary at {}, build the solvers first\n", binary.display());
+            day_start = day_end;
+            continue;
+        }
+
+        let mut runs = vec!();
+        for (_, input) in &inputs[day_start..day_end] {
+            fs::copy(input, scratch.join(format!("inputs/day{day}_input.txt")))?;
+            runs.push(run_one(&binary, &scratch, input, timeout)?);
+        }
+        print_day(day, dir, &runs);
+        all_ok &= runs.iter().all(|r| r.status == Status::Ok);
+        day_start = day_end;
+    }
+
+    fs::remove_dir_all(&scratch)?;
+    Ok(all_ok)
+}
+
+/// Where the solver for `day` lives, the day binaries are built alongside this one
+pub fn solver_path(day: u8) -> io::Result<PathBuf> {
+    let bin_dir = env::current_exe()?.parent().map(Path::to_path_buf).unwrap_or_default();
+    Ok(bin_dir.join(format!("day{day}")))
+}
+
+fn find_inputs(dir: &Path, inputs: &mut Vec<(u8, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_inputs(&path, inputs)?;
+        } else if let Some(day) = path.file_name().and_then(|n| n.to_str()).and_then(input_day) {
+            inputs.push((day, path));
+        }
+    }
+    Ok(())
+}
+
+// dayN_<anything>.txt
+fn input_day(file_name: &str) -> Option<u8> {
+    let (day, rest) = file_name.strip_prefix("day")?.split_once('_')?;
+    if !rest.ends_with(".txt") {
+        return None;
+    }
+    day.parse().ok().filter(|d| (1..=25).contains(d))
+}
+
+fn run_one(binary: &Path, scratch: &Path, input: &Path, timeout: Duration) -> io::Result<Run> {
+    let start = Instant::now();
+    let mut child = Command::new(binary)
+        .current_dir(scratch)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Drain stdout as we go so a chatty solver can't block on a full pipe
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        out
+    });
+
+    let status = loop {
+        if let Some(exit) = child.try_wait()? {
+            break if exit.success() { Status::Ok } else { Status::Panic };
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            break Status::Timeout;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let elapsed = start.elapsed();
+    let output = reader.join().unwrap_or_default();
+
+    let answers = parse_answers(&output).into_iter()
+        .map(|(answer, extra_lines)| if extra_lines > 0 { format!("{answer}<{extra_lines} lines>") } else { answer.to_string() })
+        .collect();
+
+    Ok(Run { input: input.to_path_buf(), status, elapsed, answers })
+}
+
+// "D4P1: 2" and "P1: 2" both hold the answer "2"
+fn answer_of(line: &str) -> Option<&str> {
+    let (label, answer) = line.split_once(':')?;
+    let part = label.trim_start_matches('D').trim_start_matches(|c: char| c.is_ascii_digit()).strip_prefix('P')?;
+    let is_part = !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    is_part.then(|| answer.trim().trim_matches('"'))
+}
+
+/// Picks the answers out of a solver's output, in the order they are printed, along with how many
+/// more lines each answer spans
+pub fn parse_answers(output: &str) -> Vec<(&str, usize)> {
+    let mut answers: Vec<(&str, usize)> = vec!();
+    for line in output.lines() {
+        if let Some(answer) = answer_of(line) {
+            answers.push((answer, 0));
+        } else if let Some((_, extra_lines)) = answers.last_mut() {
+            *extra_lines += 1;
+        }
+    }
+    answers
+}
+
+fn print_day(day: u8, dir: &Path, runs: &[Run]) {
+    let names: Vec<String> = runs.iter()
+        .map(|r| r.input.strip_prefix(dir).unwrap_or(&r.input).display().to_string())
+        .collect();
+    let name_width = names.iter().map(String::len).max().unwrap_or(0).max("input".len());
+    let part_count = runs.iter().map(|r| r.answers.len()).max().unwrap_or(0);
+
+    let mut header = format!("day{day:<3} {:<name_width$}  {:<7}  {:>10}", "input", "status", "time");
+    for part in 1..=part_count {
+        header += &format!("  P{part:<18}");
+    }
+    println!("{}", header.trim_end());
+    for (run, name) in runs.iter().zip(names.iter()) {
+        let status = match run.status {
+            Status::Ok => "ok",
+            Status::Panic => "panic",
+            Status::Timeout => "timeout",
+        };
+        let mut row = format!("       {name:<name_width$}  {status:<7}  {:>10}", format!("{:.2?}", run.elapsed));
+        for answer in run.answers.iter() {
+            row += &format!("  {answer:<19}");
+        }
+        println!("{}", row.trim_end());
+    }
+
+    let panics = runs.iter().filter(|r| r.status == Status::Panic).count();
+    let timeouts = runs.iter().filter(|r| r.status == Status::Timeout).count();
+    if panics > 0 || timeouts > 0 {
+        println!("  !! day{day} panicked on {panics} and timed out on {timeouts} of {} inputs", runs.len());
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_names() {
+        assert_eq!(input_day("day16_alice.txt"), Some(16));
+        assert_eq!(input_day("day3_input_test.txt"), Some(3));
+        assert_eq!(input_day("day3.txt"), None);
+        assert_eq!(input_day("day26_bob.txt"), None);
+        assert_eq!(input_day("day4_bob.txt.bak"), None);
+    }
+
+    #[test]
+    fn answers_in_print_order() {
+        let output = "Elapsed: 1.2ms\nD6P1: 7\nD6P1: 19\n";
+        assert_eq!(parse_answers(output), [("7", 0), ("19", 0)]);
+        let output = "Elapsed: 3µs\nD10P1: 13140\nD10P2: \n##..\n#..#\n";
+        assert_eq!(parse_answers(output), [("13140", 0), ("", 2)]);
+        assert_eq!(parse_answers("Elapsed: 3µs\nP1: \"2=-1=0\"\n"), [("2=-1=0", 0)]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashMap;
+
+use super::{Cube, Face, OFFSETS};
+
+// The corners of a face, counter-clockwise when looking at it from outside the cube
+fn corners((Cube(x, y, z), i): Face) -> [[i32; 3]; 4] {
+    let axis = i / 2;
+    let outwards = OFFSETS[i].0 + OFFSETS[i].1 + OFFSETS[i].2 > 0;
+    // The other two axes in cyclic order, so stepping along the first then the second turns
+    // counter-clockwise seen from the positive end of `axis`
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let mut base = [x, y, z];
+    base[axis] += outwards as i32;
+    let mut corners = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(du, dv)| {
+        let mut corner = base;
+        corner[u] += du;
+        corner[v] += dv;
+        corner
+    });
+    if !outwards {
+        corners.reverse();
+    }
+    corners
+}
+
+/// Writes the faces as a Wavefront OBJ mesh of quads, sharing vertices between faces.
+pub fn render_obj(faces: &[Face]) -> String {
+    let mut vertices = FxHashMap::default();
+    let mut vertex_lines = String::new();
+    let mut face_lines = String::new();
+    for &face in faces {
+        let indices = corners(face).map(|corner| {
+            let next = vertices.len() + 1;
+            *vertices.entry(corner).or_insert_with(|| {
+                writeln!(vertex_lines, "v {} {} {}", corner[0], corner[1], corner[2]).unwrap();
+                next
+            })
+        });
+        writeln!(face_lines, "f {} {} {} {}", indices[0], indices[1], indices[2], indices[3]).unwrap();
+    }
+    format!("o droplet\n{vertex_lines}{face_lines}")
+}
+
+/// Writes the faces as an ASCII STL mesh, two triangles to a face.
+pub fn render_stl(faces: &[Face]) -> String {
+    let mut stl = "solid droplet\n".to_string();
+    for &face in faces {
+        let (_, i) = face;
+        let [a, b, c, d] = corners(face);
+        for triangle in [[a, b, c], [a, c, d]] {
+            writeln!(stl, "facet normal {} {} {}", OFFSETS[i].0, OFFSETS[i].1, OFFSETS[i].2).unwrap();
+            stl.push_str("  outer loop\n");
+            for [x, y, z] in triangle {
+                writeln!(stl, "    vertex {x} {y} {z}").unwrap();
+            }
+            stl.push_str("  endloop\nendfacet\n");
+        }
+    }
+    stl.push_str("endsolid droplet\n");
+    stl
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashSet;
+
+    use super::*;
+    use super::super::{exterior_faces, parse_input};
+
+    #[test]
+    fn closed_meshes() {
+        let faces = exterior_faces(&parse_input(include_str!("../inputs/day18_input_test.txt")).unwrap());
+
+        // A closed surface with every face turned outwards crosses each edge once either way
+        let mut edges: FxHashMap<([i32; 3], [i32; 3]), i32> = FxHashMap::default();
+        for &face in faces.iter() {
+            let corners = corners(face);
+            for j in 0..4 {
+                let (from, to) = (corners[j], corners[(j + 1) % 4]);
+                *edges.entry((from.min(to), from.max(to))).or_default() += if from < to { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&count| count == 0));
+
+        let obj = render_obj(&faces);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 58);
+        let stl = render_stl(&faces);
+        assert_eq!(stl.matches("facet normal").count(), 58 * 2);
+        assert!(stl.starts_with("solid droplet\nfacet normal ") && stl.ends_with("endsolid droplet\n"));
+    }
+
+    #[test]
+    fn faces_point_outwards() {
+        let mut cubes = FxHashSet::default();
+        cubes.insert(Cube(0, 0, 0));
+        for face in exterior_faces(&cubes) {
+            let (_, i) = face;
+            let corners = corners(face);
+            // The cross product of two edges follows the right hand rule
+            let edge = |a: [i32; 3], b: [i32; 3]| [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let (p, q) = (edge(corners[0], corners[1]), edge(corners[1], corners[2]));
+            let normal = (p[1] * q[2] - p[2] * q[1], p[2] * q[0] - p[0] * q[2], p[0] * q[1] - p[1] * q[0]);
+            assert_eq!(normal, OFFSETS[i]);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// The letters AoC draws on screens, in the 4x6 font and the larger 6x10 one. Only the letters
+// that have turned up in puzzles are known
+const SMALL_FONT: [(char, &[&str]); 18] = [
+    ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', &["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+const LARGE_FONT: [(char, &[&str]); 15] = [
+    ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', &["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', &[".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', &[".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', &["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', &["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', &["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', &["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', &["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', &["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+type Bitmap = Vec<Vec<bool>>;
+
+fn font_for(height: usize) -> Option<&'static [(char, &'static [&'static str])]> {
+    match height {
+        6 => Some(&SMALL_FONT),
+        10 => Some(&LARGE_FONT),
+        _ => None,
+    }
+}
+
+fn glyph_bitmap(rows: &[&str]) -> Bitmap {
+    rows.iter().map(|row| row.chars().map(|c| c == '#').collect()).collect()
+}
+
+fn show(bitmap: &Bitmap) -> String {
+    bitmap.iter()
+        .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Lays letters out the way AoC does, in fixed-width cells
+fn draw(text: &str, cell_width: usize, font: &[(char, &[&str])]) -> Result<String, String> {
+    let height = font[0].1.len();
+    let mut rows = vec![String::new(); height];
+    for letter in text.chars() {
+        let (_, glyph) = font.iter()
+            .find(|(l, _)| *l == letter)
+            .ok_or_else(|| format!("There is no glyph for {letter:?}"))?;
+        for (row, glyph_row) in rows.iter_mut().zip(glyph.iter()) {
+            *row += &format!("{:<cell_width$}", glyph_row.replace('.', " "));
+        }
+    }
+    Ok(rows.join("\n"))
+}
+
+/// Draws `text` in the small font the way a day10 screen shows it, the inverse of `recognise`
+pub fn render(text: &str) -> Result<String, String> {
+    draw(text, 5, &SMALL_FONT)
+}
+
+/// Reads the letters off a screen, given as one line per row with `#` for lit pixels. Letters are
+/// told apart by the blank columns between them.
+pub fn recognise(image: &str) -> Result<String, String> {
+    let rows: Vec<Vec<bool>> = image.trim_matches('\n')
+        .lines()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect();
+    let font = font_for(rows.len())
+        .ok_or_else(|| format!("Expected a screen 6 or 10 rows tall, got {} rows", rows.len()))?;
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let lit = |r: usize, c: usize| rows[r].get(c).copied().unwrap_or(false);
+    let column_lit = |c: usize| (0..rows.len()).any(|r| lit(r, c));
+
+    let mut letters = String::new();
+    let mut c = 0;
+    while c < width {
+        if !column_lit(c) {
+            c += 1;
+            continue;
+        }
+        let start = c;
+        while c < width && column_lit(c) {
+            c += 1;
+        }
+        let glyph: Bitmap = (0..rows.len()).map(|r| (start..c).map(|c| lit(r, c)).collect()).collect();
+        let letter = font.iter()
+            .find(|(_, rows)| glyph_bitmap(rows) == glyph)
+            .map(|&(letter, _)| letter)
+            .ok_or_else(|| format!("Unrecognised glyph at column {start}:\n{}", show(&glyph)))?;
+        letters.push(letter);
+    }
+    Ok(letters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_both_fonts() {
+        assert_eq!(recognise(&render("PZGPKPEB").unwrap()), Ok("PZGPKPEB".to_string()));
+        let every_small: String = SMALL_FONT.iter().map(|(l, _)| l).collect();
+        assert_eq!(recognise(&draw(&every_small, 6, &SMALL_FONT).unwrap()), Ok(every_small));
+        let every_large: String = LARGE_FONT.iter().map(|(l, _)| l).collect();
+        assert_eq!(recognise(&draw(&every_large, 8, &LARGE_FONT).unwrap()), Ok(every_large));
+    }
+
+    #[test]
+    fn unknown_glyphs_are_errors() {
+        let image = format!("\n{}", render("AB").unwrap().replacen("#", " ", 1));
+        assert!(recognise(&image).unwrap_err().starts_with("Unrecognised glyph at column 0"));
+        assert!(recognise("##\n##").is_err());
+        assert!(render("AQ").is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::matrix;
+
+const SESSION_COOKIE_ENV_VAR: &str = "AOC_SESSION_COOKIE";
+lazy_static! {
+    static ref WAIT_REGEX: Regex = Regex::new(r"You have (?:(?P<minutes>\d+)m )?(?P<seconds>\d+)s left to wait").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    RateLimited(Duration),
+    // The part was already solved, or part one hasn't been yet
+    WrongLevel,
+    Unknown(String),
+}
+
+impl Outcome {
+    fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Wrong)
+    }
+
+    fn to_log(&self) -> String {
+        match self {
+            Outcome::Correct => "correct".to_string(),
+            Outcome::TooHigh => "too_high".to_string(),
+            Outcome::TooLow => "too_low".to_string(),
+            Outcome::Wrong => "wrong".to_string(),
+            Outcome::RateLimited(wait) => format!("rate_limited:{}", wait.as_secs()),
+            Outcome::WrongLevel => "wrong_level".to_string(),
+            Outcome::Unknown(_) => "unknown".to_string(),
+        }
+    }
+
+    fn from_log(in_str: &str) -> Option<Self> {
+        match in_str.split_once(':') {
+            Some(("rate_limited", secs)) => secs.parse().ok().map(|s| Outcome::RateLimited(Duration::from_secs(s))),
+            Some(_) => None,
+            None => match in_str {
+                "correct" => Some(Outcome::Correct),
+                "too_high" => Some(Outcome::TooHigh),
+                "too_low" => Some(Outcome::TooLow),
+                "wrong" => Some(Outcome::Wrong),
+                "wrong_level" => Some(Outcome::WrongLevel),
+                "unknown" => Some(Outcome::Unknown(String::new())),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "That's the right answer"),
+            Outcome::TooHigh => write!(f, "Wrong, the answer is too high"),
+            Outcome::TooLow => write!(f, "Wrong, the answer is too low"),
+            Outcome::Wrong => write!(f, "Wrong"),
+            Outcome::RateLimited(wait) => write!(f, "Answered too recently, wait {}s", wait.as_secs()),
+            Outcome::WrongLevel => write!(f, "This part is already solved or not unlocked yet"),
+            Outcome::Unknown(text) => write!(f, "Unrecognised response: {text}"),
+        }
+    }
+}
+
+struct Submission {
+    day: u8,
+    part: u8,
+    answer: String,
+    outcome: Outcome,
+}
+
+// Each line is "<unix time>\t<day>\t<part>\t<answer>\t<outcome>"
+fn read_log(path: &Path) -> Result<Vec<Submission>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec!()),
+        Err(e) => return Err(format!("Could not read {}: {e}", path.display())),
+    };
+    contents.lines()
+        .map(|line| parse_log_line(line).ok_or_else(|| format!("Malformed submissions log line {line:?}")))
+        .collect()
+}
+
+fn parse_log_line(line: &str) -> Option<Submission> {
+    let [_, day, part, answer, outcome] = line.split('\t').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    Some(Submission {
+        day: day.parse().ok()?,
+        part: part.parse().ok()?,
+        answer: answer.to_string(),
+        outcome: Outcome::from_log(outcome)?,
+    })
+}
+
+fn append_log(path: &Path, day: u8, part: u8, answer: &str, outcome: &Outcome) -> io::Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let mut log = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(log, "{now}\t{day}\t{part}\t{answer}\t{}", outcome.to_log())
+}
+
+// Why the site would reject this answer without us asking, going by earlier submissions
+fn known_rejection(log: &[Submission], day: u8, part: u8, answer: &str) -> Option<String> {
+    let previous = log.iter().filter(|s| s.day == day && s.part == part);
+    let value: Option<i128> = answer.parse().ok();
+    for submission in previous {
+        let already = match &submission.outcome {
+            Outcome::Correct => Some(format!("{} was already accepted", submission.answer)),
+            outcome if outcome.is_wrong() && submission.answer == answer => Some(format!("{answer} was already rejected")),
+            Outcome::TooHigh => value.zip(submission.answer.parse::<i128>().ok())
+                .filter(|(v, high)| v >= high)
+                .map(|_| format!("{} was already too high", submission.answer)),
+            Outcome::TooLow => value.zip(submission.answer.parse::<i128>().ok())
+                .filter(|(v, low)| v <= low)
+                .map(|_| format!("{} was already too low", submission.answer)),
+            _ => None,
+        };
+        if already.is_some() {
+            return already;
+        }
+    }
+    None
+}
+
+fn parse_outcome(html: &str) -> Outcome {
+    // The message lives in the page's only <article>
+    let article = html.split_once("<article")
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .and_then(|(_, rest)| rest.split_once("</article>"))
+        .map_or(html, |(article, _)| article);
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("too high") {
+            Outcome::TooHigh
+        } else if text.contains("too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Wrong
+        }
+    } else if text.contains("You gave an answer too recently") {
+        let wait = WAIT_REGEX.captures(&text).map_or(0, |captures| {
+            let minutes: u64 = captures.name("minutes").map_or(0, |m| m.as_str().parse().unwrap_or(0));
+            let seconds: u64 = captures["seconds"].parse().unwrap_or(0);
+            minutes * 60 + seconds
+        });
+        Outcome::RateLimited(Duration::from_secs(wait))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unknown(text)
+    }
+}
+
+fn post_answer(base_url: &str, year: u16, day: u8, part: u8, answer: &str, session_cookie: &str) -> Result<Outcome, String> {
+    let url = format!("{}/{year}/day/{day}/answer", base_url.trim_end_matches('/'));
+    let response = ureq::post(&url)
+        .set("cookie", &format!("session={session_cookie}"))
+        .send_form(&[("level", &part.to_string()), ("answer", answer)])
+        .map_err(|e| format!("Could not submit the answer: {e}"))?;
+    let body = response.into_string().map_err(|e| format!("Could not read the response from {url}: {e}"))?;
+    Ok(parse_outcome(&body))
+}
+
+// Runs the day's solver on its usual input and takes the answer for `part`
+fn compute_answer(day: u8, part: u8) -> Result<String, String> {
+    let solver = matrix::solver_path(day).map_err(|e| e.to_string())?;
+    let output = Command::new(&solver).output()
+        .map_err(|e| format!("Could not run {}, build the solvers first: {e}", solver.display()))?;
+    if !output.status.success() {
+        return Err(format!("day{day} did not finish successfully"));
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    match matrix::parse_answers(&output).get(part as usize - 1) {
+        Some(&(answer, 0)) if !answer.is_empty() => Ok(answer.to_string()),
+        Some(_) => Err(format!("day{day} part {part} spans several lines, pass it with --answer")),
+        None => Err(format!("day{day} did not print an answer for part {part}")),
+    }
+}
+
+/// Submits an answer for `day` and `part`, computing it with the solver unless one is given, and
+/// records what the site said in the submissions log.
+pub fn run(day: u8, part: u8, answer: Option<String>, year: u16, base_url: &str, log_path: &Path) -> Result<Outcome, String> {
+    let answer = match answer {
+        Some(answer) => answer,
+        None => compute_answer(day, part)?,
+    };
+    let log = read_log(log_path)?;
+    if let Some(reason) = known_rejection(&log, day, part, &answer) {
+        return Err(format!("Not submitting {answer} for day{day} part {part}: {reason}"));
+    }
+    let session_cookie = std::env::var(SESSION_COOKIE_ENV_VAR)
+        .map_err(|_| format!("Set {SESSION_COOKIE_ENV_VAR} to your session cookie to submit answers"))?;
+
+    println!("Submitting {answer} for day{day} part {part}");
+    let outcome = post_answer(base_url, year, day, part, &answer, &session_cookie)?;
+    append_log(log_path, day, part, &answer, &outcome)
+        .map_err(|e| format!("Could not record the outcome in {}: {e}", log_path.display()))?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn page(message: &str) -> String {
+        format!("<html><body><main>\n<article><p>{message}</p></article>\n</main></body></html>")
+    }
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(parse_outcome(&page("That's the right answer! You are <em>one gold star</em> closer.")), Outcome::Correct);
+        assert_eq!(parse_outcome(&page("That's not the right answer; your answer is too high. Please wait one minute.")), Outcome::TooHigh);
+        assert_eq!(parse_outcome(&page("That's not the right answer; your answer is too low.")), Outcome::TooLow);
+        assert_eq!(parse_outcome(&page("That's not the right answer. If you're stuck...")), Outcome::Wrong);
+        assert_eq!(
+            parse_outcome(&page("You gave an answer too recently; you have to wait. You have 4m 37s left to wait.")),
+            Outcome::RateLimited(Duration::from_secs(277)),
+        );
+        assert_eq!(parse_outcome(&page("You gave an answer too recently. You have 12s left to wait.")), Outcome::RateLimited(Duration::from_secs(12)));
+        assert_eq!(parse_outcome(&page("You don't seem to be solving the right level.")), Outcome::WrongLevel);
+    }
+
+    #[test]
+    fn known_wrong_answers_are_not_resubmitted() {
+        let submission = |answer: &str, outcome| Submission { day: 14, part: 2, answer: answer.to_string(), outcome };
+        let log = [submission("900", Outcome::TooLow), submission("950", Outcome::TooHigh), submission("abc", Outcome::Wrong)];
+        assert!(known_rejection(&log, 14, 2, "900").is_some());
+        assert!(known_rejection(&log, 14, 2, "850").is_some());
+        assert!(known_rejection(&log, 14, 2, "960").is_some());
+        assert!(known_rejection(&log, 14, 2, "abc").is_some());
+        assert!(known_rejection(&log, 14, 2, "925").is_none());
+        assert!(known_rejection(&log, 14, 1, "900").is_none());
+        assert!(known_rejection(&[submission("925", Outcome::Correct)], 14, 2, "926").is_some());
+    }
+
+    #[test]
+    fn posts_to_the_configured_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = vec!();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(length) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line.trim_end().to_string());
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let page = page("That's not the right answer; your answer is too low.");
+            write!(reader.get_mut(), "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{page}", page.len()).unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let outcome = post_answer(&base_url, 2022, 14, 2, "24", "abc123").unwrap();
+        let (head, body) = server.join().unwrap();
+        assert_eq!(outcome, Outcome::TooLow);
+        assert_eq!(head[0], "POST /2022/day/14/answer HTTP/1.1");
+        assert!(head.iter().any(|h| h.eq_ignore_ascii_case("cookie: session=abc123")));
+        assert_eq!(body, "level=2&answer=24");
+    }
+}